@@ -5,7 +5,7 @@ use std::io;
 async fn main() -> io::Result<()> {
     let server = Server::builder()
         .with(BasicAuth::new("user", "password", "/"))
-        .route("/", Method::Get, |_, _| async { "Hello" })
+        .route("/", Method::Get, || async { "Hello" })
         .build();
     Server::run(server, 8080).await
 }
@@ -7,10 +7,15 @@ use std::{
 };
 
 use db::Db;
-use model::{Post, User};
+use model::{Post, Posts, User};
 use qz::{
-    body::Body, method::Method, middleware::Cors, mime, redirect::Redirect, request::Request,
-    response::Response, server::Server, status::StatusCode,
+    method::Method,
+    middleware::Cors,
+    redirect::Redirect,
+    request::Request,
+    response::{Json, Response},
+    server::Server,
+    status::StatusCode,
 };
 
 async fn register(request: Request, db: Arc<RwLock<Db>>) -> qz::Result<Response> {
@@ -23,17 +28,12 @@ async fn register(request: Request, db: Arc<RwLock<Db>>) -> qz::Result<Response>
     Ok(Redirect::see_other("/posts").into())
 }
 
-async fn posts(_request: Request, db: Arc<RwLock<Db>>) -> qz::Result<Response> {
+async fn posts(_request: Request, db: Arc<RwLock<Db>>) -> qz::Result<Json<Posts>> {
     let db = db
         .as_ref()
         .read()
         .or(Err(StatusCode::InternalServerError))?;
-    let posts = db.current_posts(10);
-    let body = Body::from_json(&posts)?;
-    Ok(Response::builder()
-        .set_body(body)
-        .set_content_type(mime::APPLICATION_JSON)
-        .build())
+    Ok(Json(db.current_posts(10)))
 }
 
 async fn create_post(request: Request, db: Arc<RwLock<Db>>) -> qz::Result<Response> {
@@ -1,7 +1,7 @@
-use qz::{method::Method, request::Request, response::Response, server::Server};
+use qz::{method::Method, request::Request, response::IntoResponse, server::Server};
 use std::io;
 
-async fn hello(_request: Request, _: ()) -> impl Into<Response> {
+async fn hello(_request: Request, _: ()) -> impl IntoResponse {
     "hello"
 }
 
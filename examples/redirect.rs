@@ -4,7 +4,7 @@ use std::io;
 #[tokio::main]
 async fn main() -> io::Result<()> {
     let server = Server::builder()
-        .route("/hello", Method::Get, |_, _| async { "Hello" })
+        .route("/hello", Method::Get, || async { "Hello" })
         .route(
             "/obsolete",
             Method::Get,
@@ -1,13 +1,13 @@
 use qz::{
-    method::Method, request::Request, response::Response, server::Server, status::StatusCode,
+    method::Method,
+    response::{Html, IntoResponse},
+    server::Server,
+    status::StatusCode,
 };
 use std::io;
 
-async fn teapot(_request: Request, _: ()) -> impl Into<Response> {
-    Response::builder()
-        .set_status_code(StatusCode::ImaTeapot)
-        .set_body("<h1>I'm a Teapot;)</h1>")
-        .build()
+async fn teapot() -> impl IntoResponse {
+    (StatusCode::ImaTeapot, Html("<h1>I'm a Teapot;)</h1>"))
 }
 
 #[tokio::main]
@@ -20,7 +20,7 @@ async fn main() -> io::Result<()> {
     let port = 8080;
     let server = Server::builder()
         .with(BasicAuth::new("user", "password", "/hello"))
-        .route("/", Method::Get, |_, _| async { "It works!" })
+        .route("/", Method::Get, || async { "It works!" })
         // .serve_dir("/", "./html")
         .route("/teapot", Method::Get, teapot)
         .route("/echo", Method::Post, echo)
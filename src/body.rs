@@ -1,17 +1,34 @@
-use crate::{response::Response, status::StatusCode};
+use crate::{response::Response, status::StatusCode, Error};
 use serde::{de::DeserializeOwned, Serialize};
-use std::{convert::From, fmt};
+use std::{convert::From, fmt, pin::Pin, sync::Arc};
+use tokio::{io::AsyncRead, sync::Mutex};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum Body {
+    #[default]
     None,
     Some(Vec<u8>),
+    /// Body read from `AsyncRead` while sending a response.
+    Stream(BodyStream),
 }
 
 impl Body {
+    /// Create a body streamed from `reader`. The length of the body is unknown, so it is sent
+    /// with `Transfer-Encoding: chunked`.
+    pub fn from_reader(reader: impl AsyncRead + Send + 'static) -> Self {
+        Body::Stream(BodyStream::new(reader, None))
+    }
+
+    /// Create a body streamed from `reader` which yields `len` bytes.
+    pub fn from_reader_with_len(reader: impl AsyncRead + Send + 'static, len: u64) -> Self {
+        Body::Stream(BodyStream::new(reader, Some(len)))
+    }
+
+    /// Length of the body. Length of a stream whose length is unknown is 0.
     pub fn len(&self) -> usize {
         match &self {
             Body::Some(bytes) => bytes.len(),
+            Body::Stream(stream) => stream.length().unwrap_or(0) as usize,
             Body::None => 0,
         }
     }
@@ -22,14 +39,14 @@ impl Body {
 
     pub fn is_some(&self) -> bool {
         match &self {
-            Body::Some(_) => true,
+            Body::Some(_) | Body::Stream(_) => true,
             Body::None => false,
         }
     }
 
     pub fn is_none(&self) -> bool {
         match &self {
-            Body::Some(_) => false,
+            Body::Some(_) | Body::Stream(_) => false,
             Body::None => true,
         }
     }
@@ -52,12 +69,8 @@ impl Body {
     /// assert_eq!("qwerty", &user.password);
     /// ```
     pub fn into_form<T: DeserializeOwned>(&self) -> crate::Result<T> {
-        match &self {
-            Body::Some(bytes) => {
-                serde_urlencoded::from_bytes::<T>(bytes).or(Err(StatusCode::BadRequest))
-            }
-            Body::None => Err(StatusCode::InternalServerError),
-        }
+        let bytes = self.as_parsable()?;
+        Ok(serde_urlencoded::from_bytes::<T>(bytes)?)
     }
 
     /// Parse `Body` as `application/json` data.
@@ -77,7 +90,9 @@ impl Body {
     /// assert_eq!(Body::from(r#"{"username":"John","password":"qwerty"}"#), body);
     /// ```
     pub fn from_json(json: &impl Serialize) -> crate::Result<Body> {
-        let bytes = serde_json::to_vec(&json).or(Err(StatusCode::BadRequest))?;
+        let bytes = serde_json::to_vec(&json).map_err(|err| {
+            Error::new(StatusCode::InternalServerError, "failed to serialize JSON").set_source(err)
+        })?;
         Ok(Body::from(bytes))
     }
 
@@ -99,25 +114,26 @@ impl Body {
     /// assert_eq!("qwerty", &user.password);
     /// ```
     pub fn into_json<T: DeserializeOwned>(&self) -> crate::Result<T> {
-        match &self {
-            Body::Some(bytes) => {
-                serde_json::from_slice::<T>(&bytes).or(Err(StatusCode::BadRequest))
-            }
-            Body::None => Err(StatusCode::InternalServerError),
-        }
+        let bytes = self.as_parsable()?;
+        Ok(serde_json::from_slice::<T>(bytes)?)
     }
-}
 
-impl Default for Body {
-    fn default() -> Self {
-        Self::None
+    fn as_parsable(&self) -> crate::Result<&[u8]> {
+        match &self {
+            Body::Some(bytes) => Ok(bytes),
+            Body::None => Err(Error::new(StatusCode::BadRequest, "request body is empty")),
+            Body::Stream(_) => Err(Error::new(
+                StatusCode::InternalServerError,
+                "streamed body cannot be parsed",
+            )),
+        }
     }
 }
 
 impl AsRef<[u8]> for Body {
     fn as_ref(&self) -> &[u8] {
         match self {
-            Self::None => &[],
+            Self::None | Self::Stream(_) => &[],
             Self::Some(bytes) => bytes,
         }
     }
@@ -156,11 +172,55 @@ impl From<Body> for Response {
 impl fmt::Display for Body {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
-            Body::Some(bytes) => match std::str::from_utf8(&bytes) {
+            Body::Some(bytes) => match std::str::from_utf8(bytes) {
                 Ok(s) => write!(f, "{}", s),
                 Err(_) => write!(f, "{:?}", bytes),
             },
+            Body::Stream(_) => write!(f, "<stream>"),
             Body::None => write!(f, ""),
         }
     }
 }
+
+/// Asynchronous reader of a body which is not buffered in memory.
+///
+/// The reader is shared among clones of the stream, so a body can be read only once.
+#[derive(Clone)]
+pub struct BodyStream {
+    reader: Arc<Mutex<Pin<Box<dyn AsyncRead + Send>>>>,
+    len: Option<u64>,
+}
+
+impl BodyStream {
+    fn new(reader: impl AsyncRead + Send + 'static, len: Option<u64>) -> Self {
+        Self {
+            reader: Arc::new(Mutex::new(Box::pin(reader))),
+            len,
+        }
+    }
+
+    /// Length of the stream if it is known beforehand.
+    pub fn length(&self) -> Option<u64> {
+        self.len
+    }
+
+    pub(crate) async fn lock(&self) -> tokio::sync::MutexGuard<'_, Pin<Box<dyn AsyncRead + Send>>> {
+        self.reader.lock().await
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyStream")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl PartialEq for BodyStream {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.reader, &other.reader)
+    }
+}
+
+impl Eq for BodyStream {}
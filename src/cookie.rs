@@ -0,0 +1,315 @@
+mod jar;
+mod key;
+
+pub use jar::CookieJar;
+pub use key::Key;
+
+use crate::{date::HttpDate, parser::is_tchar};
+use std::{borrow::Cow, fmt, time::Duration};
+
+/// Value of `SameSite` attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// Browsers require `Secure` attribute for this.
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SameSite::Strict => write!(f, "Strict"),
+            SameSite::Lax => write!(f, "Lax"),
+            SameSite::None => write!(f, "None"),
+        }
+    }
+}
+
+/// Cookie set by a server with `Set-Cookie` header.
+///
+/// Bytes of a name which are not allowed in a token and bytes of a value which are not allowed in
+/// a cookie value, e.g. whitespaces, `"`, `,`, `;` and `\`, are percent-encoded when the cookie is
+/// sent. `Request::cookies()` decodes them.
+/// `;` and control characters in `Path` and `Domain` are percent-encoded too, so that they cannot
+/// inject attributes.
+///
+/// # Examples
+/// ```rust
+/// use qz::cookie::{Cookie, SameSite};
+/// use std::time::Duration;
+///
+/// let cookie = Cookie::new("theme", "dark")
+///     .set_path("/")
+///     .set_max_age(Duration::from_secs(3600))
+///     .set_http_only(true)
+///     .set_same_site(SameSite::Lax);
+/// assert_eq!(
+///     "theme=dark; Path=/; Max-Age=3600; HttpOnly; SameSite=Lax",
+///     cookie.to_string()
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    expires: Option<HttpDate>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            max_age: None,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Cookie which makes a client delete the cookie named `name`.
+    /// `Path` and `Domain` must be the same as ones of the cookie to delete.
+    pub fn removal(name: impl Into<String>) -> Self {
+        Self::new(name, "")
+            .set_max_age(Duration::from_secs(0))
+            .set_expires(HttpDate::from(std::time::UNIX_EPOCH))
+    }
+
+    pub fn set_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn set_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn set_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn set_expires(mut self, expires: HttpDate) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+
+    pub fn set_secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    pub fn set_http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    pub fn set_same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    pub fn expires(&self) -> Option<HttpDate> {
+        self.expires
+    }
+
+    pub fn secure(&self) -> bool {
+        self.secure
+    }
+
+    pub fn http_only(&self) -> bool {
+        self.http_only
+    }
+
+    pub fn same_site(&self) -> Option<SameSite> {
+        self.same_site
+    }
+
+    fn is_removal(&self) -> bool {
+        self.max_age == Some(Duration::from_secs(0))
+    }
+
+    fn with_value(&self, value: String) -> Self {
+        Self {
+            value,
+            ..self.clone()
+        }
+    }
+}
+
+/// Format as a value of `Set-Cookie` header.
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            percent_encode(&self.name, is_name_octet),
+            percent_encode(&self.value, is_value_octet)
+        )?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", percent_encode(path, is_attribute_octet))?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", percent_encode(domain, is_attribute_octet))?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(expires) = self.expires {
+            write!(f, "; Expires={}", expires)?;
+        }
+        if self.secure {
+            write!(f, "; Secure")?;
+        }
+        if self.http_only {
+            write!(f, "; HttpOnly")?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        Ok(())
+    }
+}
+
+// `%` is not allowed in names and values so that they can be decoded.
+pub(crate) fn is_name_octet(b: u8) -> bool {
+    is_tchar(b) && b != b'%'
+}
+
+/// `cookie-octet` in RFC 6265 except `%`.
+pub(crate) fn is_value_octet(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x2b | 0x2d..=0x3a | 0x3c..=0x5b | 0x5d..=0x7e) && b != b'%'
+}
+
+/// `av-octet` in RFC 6265, i.e. any character except control characters and `;`.
+fn is_attribute_octet(b: u8) -> bool {
+    (0x20..0x7f).contains(&b) && b != b';'
+}
+
+/// Percent-encode bytes of `s` for which `is_allowed` returns `false`.
+pub(crate) fn percent_encode(s: &str, is_allowed: fn(u8) -> bool) -> Cow<'_, str> {
+    if s.bytes().all(is_allowed) {
+        return Cow::Borrowed(s);
+    }
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if is_allowed(b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+/// Decode a name or a value encoded by `percent_encode()`. A cookie may be set by another
+/// application, so malformed sequences are kept as they are.
+pub(crate) fn percent_decode(s: &str) -> Cow<'_, str> {
+    if !s.contains('%') {
+        return Cow::Borrowed(s);
+    }
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    match String::from_utf8(decoded) {
+        Ok(decoded) => Cow::Owned(decoded),
+        Err(_) => Cow::Borrowed(s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_cookie() {
+        let cookie = Cookie::new("id", "a3fWa")
+            .set_domain("example.com")
+            .set_expires(HttpDate::from(
+                std::time::UNIX_EPOCH + Duration::from_secs(784111777),
+            ))
+            .set_secure(true)
+            .set_same_site(SameSite::Strict);
+        assert_eq!(
+            "id=a3fWa; Domain=example.com; Expires=Sun, 06 Nov 1994 08:49:37 GMT; Secure; SameSite=Strict",
+            cookie.to_string()
+        );
+    }
+
+    #[test]
+    fn format_removal() {
+        assert_eq!(
+            "id=; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+            Cookie::removal("id").to_string()
+        );
+    }
+
+    #[test]
+    fn encode_invalid_bytes() {
+        let cookie = Cookie::new("a b", "x; Domain=evil\r\nSet-Cookie: y=1, 100%")
+            .set_path("/; Secure")
+            .set_domain("example.com\r\n");
+        assert_eq!(
+            "a%20b=x%3B%20Domain=evil%0D%0ASet-Cookie:%20y=1%2C%20100%25; \
+            Path=/%3B Secure; Domain=example.com%0D%0A",
+            cookie.to_string()
+        );
+    }
+
+    #[test]
+    fn decode_percent_encoded() {
+        let value = "{\"a\": \"b;c\"}, 100%";
+        assert_eq!(
+            value,
+            percent_decode(&percent_encode(value, is_value_octet))
+        );
+        assert_eq!("%zz%+1%4", percent_decode("%zz%+1%4"));
+    }
+}
@@ -0,0 +1,153 @@
+use super::{Cookie, Key};
+use crate::header::typed;
+
+/// Cookies sent by a client and ones to set in a response.
+/// Changes made with `add()` and `remove()` are visible to `get()` and are sent with
+/// `Response::set_cookies()`.
+///
+/// # Examples
+/// ```rust
+/// use qz::{cookie::{Cookie, CookieJar}, header::typed};
+///
+/// let mut jar = CookieJar::from(&typed::Cookie::new().with("theme", "dark"));
+/// assert_eq!(Some("dark"), jar.get("theme"));
+///
+/// jar.add(Cookie::new("theme", "light"));
+/// jar.remove("lang");
+/// assert_eq!(Some("light"), jar.get("theme"));
+/// assert_eq!(2, jar.delta().count());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    original: typed::Cookie,
+    delta: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Value of the cookie named `name`. A cookie added to the jar precedes one sent by the client.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        match self.delta.iter().rev().find(|cookie| cookie.name() == name) {
+            Some(cookie) if cookie.is_removal() => None,
+            Some(cookie) => Some(cookie.value()),
+            None => self.original.get(name),
+        }
+    }
+
+    /// Add a cookie to set in the response. A cookie added before with the same name is replaced.
+    pub fn add(&mut self, cookie: Cookie) {
+        self.delta.retain(|c| c.name() != cookie.name());
+        self.delta.push(cookie);
+    }
+
+    /// Make the client delete the cookie named `name`.
+    pub fn remove(&mut self, name: &str) {
+        self.add(Cookie::removal(name));
+    }
+
+    /// Value of the signed cookie named `name`. `None` is returned if the signature is invalid.
+    pub fn get_signed(&self, name: &str, key: &Key) -> Option<String> {
+        key.verify(name, self.get(name)?)
+    }
+
+    /// Add a cookie whose value is signed with `key`. The client can read the value but cannot
+    /// modify it.
+    pub fn add_signed(&mut self, cookie: Cookie, key: &Key) {
+        let value = key.sign(cookie.name(), cookie.value());
+        self.add(cookie.with_value(value));
+    }
+
+    /// Value of the encrypted cookie named `name`. `None` is returned if it cannot be decrypted.
+    pub fn get_private(&self, name: &str, key: &Key) -> Option<String> {
+        key.decrypt(name, self.get(name)?)
+    }
+
+    /// Add a cookie whose value is encrypted with `key`. The client can neither read nor modify
+    /// the value.
+    pub fn add_private(&mut self, cookie: Cookie, key: &Key) {
+        let value = key.encrypt(cookie.name(), cookie.value());
+        self.add(cookie.with_value(value));
+    }
+
+    /// Cookies added or removed, which should be sent with `Set-Cookie` header.
+    pub fn delta(&self) -> impl Iterator<Item = &Cookie> {
+        self.delta.iter()
+    }
+}
+
+impl From<&typed::Cookie> for CookieJar {
+    fn from(original: &typed::Cookie) -> Self {
+        Self {
+            original: original.clone(),
+            delta: Vec::new(),
+        }
+    }
+}
+
+impl From<typed::Cookie> for CookieJar {
+    fn from(original: typed::Cookie) -> Self {
+        Self {
+            original,
+            delta: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jar() -> CookieJar {
+        CookieJar::from(typed::Cookie::new().with("id", "42").with("theme", "dark"))
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut jar = jar();
+        jar.add(Cookie::new("theme", "light"));
+        jar.add(Cookie::new("theme", "blue"));
+        jar.remove("id");
+        assert_eq!(None, jar.get("id"));
+        assert_eq!(Some("blue"), jar.get("theme"));
+        let delta = jar.delta().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "theme=blue",
+                "id=; Max-Age=0; Expires=Thu, 01 Jan 1970 00:00:00 GMT"
+            ],
+            delta
+        );
+    }
+
+    #[test]
+    fn signed_cookie() {
+        let key = Key::generate();
+        let mut jar = CookieJar::new();
+        jar.add_signed(Cookie::new("user", "alice").set_http_only(true), &key);
+        assert_ne!(Some("alice"), jar.get("user"));
+        assert_eq!(Some("alice".to_string()), jar.get_signed("user", &key));
+
+        let tampered = jar.get("user").unwrap().replace("alice", "admin");
+        let jar = CookieJar::from(typed::Cookie::new().with("user", tampered));
+        assert_eq!(None, jar.get_signed("user", &key));
+        // Unsigned cookie is rejected as well.
+        assert_eq!(None, self::jar().get_signed("id", &key));
+    }
+
+    #[test]
+    fn private_cookie() {
+        let key = Key::generate();
+        let mut jar = CookieJar::new();
+        jar.add_private(Cookie::new("user", "alice"), &key);
+        assert!(!jar.get("user").unwrap().contains("alice"));
+        assert_eq!(Some("alice".to_string()), jar.get_private("user", &key));
+
+        // A value encrypted for another cookie is rejected.
+        let sealed = jar.get("user").unwrap().to_string();
+        let jar = CookieJar::from(typed::Cookie::new().with("admin", sealed));
+        assert_eq!(None, jar.get_private("admin", &key));
+    }
+}
@@ -0,0 +1,151 @@
+use aes_gcm::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{convert::TryInto, fmt};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Secret key of a server to sign and encrypt cookies.
+/// The same key must be used across restarts of the server and among servers behind a load
+/// balancer, otherwise cookies issued before become invalid.
+#[derive(Clone)]
+pub struct Key {
+    signing: [u8; KEY_LEN],
+    encryption: [u8; KEY_LEN],
+}
+
+impl Key {
+    /// Create a key from 64 bytes of a random secret. The first half is used for signing and the
+    /// other is used for encryption.
+    ///
+    /// # Panics
+    /// Panics if `master` is shorter than 64 bytes.
+    pub fn from_bytes(master: &[u8]) -> Self {
+        assert!(
+            master.len() >= KEY_LEN * 2,
+            "Key must be at least {} bytes",
+            KEY_LEN * 2
+        );
+        Self {
+            signing: master[..KEY_LEN].try_into().unwrap(),
+            encryption: master[KEY_LEN..KEY_LEN * 2].try_into().unwrap(),
+        }
+    }
+
+    /// Generate a random key.
+    pub fn generate() -> Self {
+        let mut master = [0; KEY_LEN * 2];
+        OsRng.fill_bytes(&mut master);
+        Self::from_bytes(&master)
+    }
+
+    fn mac(&self, name: &str, value: &str) -> HmacSha256 {
+        let mut mac = <HmacSha256 as Mac>::new_from_slice(&self.signing).unwrap();
+        // The name is signed too, so that a value cannot be moved into another cookie.
+        mac.update(name.as_bytes());
+        mac.update(b"=");
+        mac.update(value.as_bytes());
+        mac
+    }
+
+    /// Prepend base64-encoded HMAC-SHA256 signature to `value`.
+    pub(crate) fn sign(&self, name: &str, value: &str) -> String {
+        let signature = self.mac(name, value).finalize().into_bytes();
+        let mut signed = base64::encode(signature);
+        signed.push_str(value);
+        signed
+    }
+
+    /// Verify a value made with `sign()` and return the original value.
+    pub(crate) fn verify(&self, name: &str, signed: &str) -> Option<String> {
+        // Base64 of 32 bytes is 44 characters.
+        let signature_len = SIGNATURE_LEN.div_ceil(3) * 4;
+        if !signed.is_char_boundary(signature_len) {
+            return None;
+        }
+        let (signature, value) = signed.split_at(signature_len);
+        let signature = base64::decode(signature).ok()?;
+        // `verify_slice()` compares in constant time.
+        self.mac(name, value).verify_slice(&signature).ok()?;
+        Some(value.to_string())
+    }
+
+    /// Encrypt `value` with AES-256-GCM and encode a nonce and the cipher text with base64.
+    /// The name is authenticated as associated data.
+    pub(crate) fn encrypt(&self, name: &str, value: &str) -> String {
+        let cipher = Aes256Gcm::new(&self.encryption.into());
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let payload = Payload {
+            msg: value.as_bytes(),
+            aad: name.as_bytes(),
+        };
+        // Encryption fails only if the value is too large for AES-GCM, about 64 GiB.
+        let encrypted = cipher.encrypt(&nonce, payload).unwrap();
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&encrypted);
+        base64::encode(sealed)
+    }
+
+    /// Decrypt a value made with `encrypt()`. `None` is returned if it is tampered with.
+    pub(crate) fn decrypt(&self, name: &str, sealed: &str) -> Option<String> {
+        let sealed = base64::decode(sealed).ok()?;
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, encrypted) = sealed.split_at(NONCE_LEN);
+        let cipher = Aes256Gcm::new(&self.encryption.into());
+        let payload = Payload {
+            msg: encrypted,
+            aad: name.as_bytes(),
+        };
+        let value = cipher.decrypt(Nonce::from_slice(nonce), payload).ok()?;
+        String::from_utf8(value).ok()
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the secret.
+        write!(f, "Key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_and_verify() {
+        let key = Key::generate();
+        let signed = key.sign("id", "42");
+        assert_eq!(Some("42".to_string()), key.verify("id", &signed));
+        assert_eq!(None, key.verify("other", &signed));
+        assert_eq!(None, key.verify("id", &signed.replace("42", "43")));
+        assert_eq!(None, Key::generate().verify("id", &signed));
+        assert_eq!(None, key.verify("id", "short"));
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = Key::generate();
+        let sealed = key.encrypt("id", "42");
+        assert_ne!(sealed, key.encrypt("id", "42"));
+        assert_eq!(Some("42".to_string()), key.decrypt("id", &sealed));
+        assert_eq!(None, key.decrypt("other", &sealed));
+        assert_eq!(None, Key::generate().decrypt("id", &sealed));
+        assert_eq!(None, key.decrypt("id", "AAAA"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn short_key() {
+        Key::from_bytes(&[0; 63]);
+    }
+}
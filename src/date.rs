@@ -0,0 +1,172 @@
+use crate::status::StatusCode;
+use std::{
+    convert::TryFrom,
+    fmt, str,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Date used in HTTP headers such as `Last-Modified`, with precision of a second.
+/// It is formatted in IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate {
+    // Seconds since UNIX epoch.
+    secs: u64,
+}
+
+impl HttpDate {
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+
+    /// Seconds since UNIX epoch.
+    pub fn as_secs(&self) -> u64 {
+        self.secs
+    }
+}
+
+impl From<SystemTime> for HttpDate {
+    fn from(time: SystemTime) -> Self {
+        // Dates before UNIX epoch are not expected in HTTP.
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        Self { secs }
+    }
+}
+
+impl From<HttpDate> for SystemTime {
+    fn from(date: HttpDate) -> Self {
+        UNIX_EPOCH + Duration::from_secs(date.secs)
+    }
+}
+
+/// Convert days since UNIX epoch into (year, month, day).
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Convert (year, month, day) into days since UNIX epoch.
+/// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let days = (self.secs / 86400) as i64;
+        let secs_of_day = self.secs % 86400;
+        let (year, month, day) = civil_from_days(days);
+        // 1970-01-01 is Thursday.
+        let weekday = WEEKDAYS[(days + 3).rem_euclid(7) as usize];
+        write!(
+            f,
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            weekday,
+            day,
+            MONTHS[month as usize - 1],
+            year,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        )
+    }
+}
+
+/// Parse IMF-fixdate. Obsolete formats(RFC 850 and asctime) are not supported.
+impl TryFrom<&[u8]> for HttpDate {
+    type Error = StatusCode;
+
+    fn try_from(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let value = str::from_utf8(value).or(Err(StatusCode::BadRequest))?;
+        let parts = value.split(' ').collect::<Vec<_>>();
+        let (day, month, year, time) = match parts[..] {
+            [weekday, day, month, year, time, "GMT"]
+                if weekday.ends_with(',') && WEEKDAYS.contains(&&weekday[..3]) =>
+            {
+                (day, month, year, time)
+            }
+            _ => return Err(StatusCode::BadRequest),
+        };
+        let parse = |s: &str, len: usize| -> crate::Result<u64, StatusCode> {
+            if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(StatusCode::BadRequest);
+            }
+            s.parse().or(Err(StatusCode::BadRequest))
+        };
+
+        let day = parse(day, 2)? as u32;
+        let month = MONTHS
+            .iter()
+            .position(|&m| m == month)
+            .ok_or(StatusCode::BadRequest)? as u32
+            + 1;
+        let year = parse(year, 4)? as i64;
+        let time = time.split(':').collect::<Vec<_>>();
+        let (hour, minute, second) = match time[..] {
+            [hour, minute, second] => (parse(hour, 2)?, parse(minute, 2)?, parse(second, 2)?),
+            _ => return Err(StatusCode::BadRequest),
+        };
+        if year < 1970 || day == 0 || day > 31 || hour > 23 || minute > 59 || second > 60 {
+            return Err(StatusCode::BadRequest);
+        }
+
+        let days = days_from_civil(year, month, day) as u64;
+        Ok(Self {
+            secs: days * 86400 + hour * 3600 + minute * 60 + second,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_date() {
+        let date = HttpDate::from(UNIX_EPOCH + Duration::from_secs(784111777));
+        assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", date.to_string());
+    }
+
+    #[test]
+    fn parse_date() {
+        assert_eq!(
+            Ok(HttpDate { secs: 784111777 }),
+            HttpDate::try_from(&b"Sun, 06 Nov 1994 08:49:37 GMT"[..])
+        );
+        assert_eq!(
+            Ok(HttpDate { secs: 951782400 }),
+            HttpDate::try_from(&b"Tue, 29 Feb 2000 00:00:00 GMT"[..])
+        );
+        assert!(HttpDate::try_from(&b"Sunday, 06-Nov-94 08:49:37 GMT"[..]).is_err());
+    }
+
+    #[test]
+    fn format_and_parse() {
+        let date = HttpDate::now();
+        assert_eq!(Ok(date), HttpDate::try_from(date.to_string().as_bytes()));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::{
+    header::{typed::Accept, HeaderName},
+    response::{IntoResponse, Response},
+    status::StatusCode,
+};
+use serde_json::{json, Map, Value};
+use std::{error, fmt, io, str};
+
+const APPLICATION_PROBLEM_JSON: &str = "application/problem+json";
+
+/// Error returned from handlers and extractors. It is sent as a problem detail of RFC 7807
+/// (`application/problem+json`), or as plain text if the client prefers `text/plain` in `Accept`.
+///
+/// `message` is sent to the client as `detail`, so it must not contain secrets. `source` is never
+/// sent.
+///
+/// # Examples
+/// ```rust
+/// use qz::{status::StatusCode, Error};
+/// use serde_json::json;
+///
+/// let error = Error::new(StatusCode::BadRequest, "`name` is too long")
+///     .set_details(json!({ "max_length": 32 }));
+/// assert_eq!(StatusCode::BadRequest, error.status());
+/// assert_eq!(Some("`name` is too long"), error.message());
+/// ```
+#[derive(Debug)]
+pub struct Error {
+    status: StatusCode,
+    message: Option<String>,
+    source: Option<Box<dyn error::Error + Send + Sync>>,
+    details: Option<Value>,
+}
+
+impl Error {
+    pub fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: Some(message.into()),
+            source: None,
+            details: None,
+        }
+    }
+
+    /// Set the underlying error, which is useful for logging.
+    pub fn set_source(mut self, source: impl error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Set additional information. Members of an object are sent as extension members of the
+    /// problem detail, and other values are sent as `details` member.
+    pub fn set_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub(crate) fn set_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    pub fn details(&self) -> Option<&Value> {
+        self.details.as_ref()
+    }
+
+    fn title(&self) -> &'static str {
+        // Reason phrases are ASCII.
+        str::from_utf8(self.status.reason_phrase()).unwrap()
+    }
+
+    fn to_problem_json(&self) -> Vec<u8> {
+        let mut problem = Map::new();
+        if let Some(details) = &self.details {
+            match details {
+                Value::Object(members) => problem.extend(members.clone()),
+                details => {
+                    problem.insert("details".to_string(), details.clone());
+                }
+            }
+        }
+        // Standard members cannot be overridden by details.
+        problem.insert("type".to_string(), json!("about:blank"));
+        problem.insert("title".to_string(), json!(self.title()));
+        problem.insert("status".to_string(), json!(self.status.code()));
+        if let Some(message) = &self.message {
+            problem.insert("detail".to_string(), json!(message));
+        }
+        // Serializing a map of `Value` never fails.
+        serde_json::to_vec(&problem).unwrap()
+    }
+
+    fn to_plain_text(&self) -> Vec<u8> {
+        match &self.message {
+            Some(message) => format!("{}: {}", self.title(), message).into_bytes(),
+            None => self.title().as_bytes().to_vec(),
+        }
+    }
+
+    /// Replace the body of `response` with this error in the format which the client prefers.
+    pub(crate) fn render(&self, response: &mut Response, accept: Option<&Accept>) {
+        let plain = accept
+            .and_then(|accept| {
+                accept.negotiate(&[APPLICATION_PROBLEM_JSON, "application/json", "text/plain"])
+            })
+            .is_some_and(|mime_type| mime_type == "text/plain");
+        let (content_type, body) = if plain {
+            ("text/plain; charset=utf-8", self.to_plain_text())
+        } else {
+            (APPLICATION_PROBLEM_JSON, self.to_problem_json())
+        };
+        response.set_header(HeaderName::ContentType, content_type);
+        response.set_body(body);
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status.code(), self.title())?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+/// Errors are equal if they would be sent as the same response.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.status == other.status
+            && self.message == other.message
+            && self.details == other.details
+    }
+}
+
+impl From<StatusCode> for Error {
+    fn from(status: StatusCode) -> Self {
+        Self {
+            status,
+            message: None,
+            source: None,
+            details: None,
+        }
+    }
+}
+
+/// The message of `io::Error` is not sent because it can contain a path of the server.
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::from(StatusCode::from(err.kind())).set_source(err)
+    }
+}
+
+/// Deserialization error of a request body.
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::new(StatusCode::BadRequest, err.to_string()).set_source(err)
+    }
+}
+
+/// Deserialization error of a query string, a form or path parameters.
+impl From<serde_urlencoded::de::Error> for Error {
+    fn from(err: serde_urlencoded::de::Error) -> Self {
+        Error::new(StatusCode::BadRequest, err.to_string()).set_source(err)
+    }
+}
+
+/// The response is sent as `application/problem+json`. `Server` sends it as plain text instead if
+/// the client prefers it.
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(self.status);
+        self.render(&mut response, None);
+        response.insert_extension(self);
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::Body, header::typed::TypedHeader};
+
+    #[test]
+    fn problem_json() {
+        let error = Error::new(StatusCode::BadRequest, "missing field `name`")
+            .set_details(json!({ "field": "name", "status": 999 }));
+        let response = error.into_response();
+        assert_eq!(StatusCode::BadRequest, response.status_code());
+        assert_eq!(
+            Some(&b"application/problem+json".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+        let body: Value = response.body().into_json().unwrap();
+        assert_eq!(
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "missing field `name`",
+                "field": "name",
+            }),
+            body
+        );
+    }
+
+    #[test]
+    fn plain_text() {
+        let error = Error::from(StatusCode::NotFound);
+        let mut response = Response::new(error.status());
+        let accept = Accept::decode(b"text/plain, application/json;q=0.5").unwrap();
+        error.render(&mut response, Some(&accept));
+        assert_eq!(&Body::from("Not Found"), response.body());
+
+        let accept = Accept::decode(b"text/html, */*;q=0.8").unwrap();
+        error.render(&mut response, Some(&accept));
+        assert_eq!(
+            Some(&b"application/problem+json".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+    }
+
+    #[test]
+    fn convert_errors() {
+        let err = serde_json::from_str::<u32>("\"a\"").unwrap_err();
+        let error = Error::from(err);
+        assert_eq!(StatusCode::BadRequest, error.status());
+        assert!(error.message().unwrap().contains("invalid type"));
+        assert!(error::Error::source(&error).is_some());
+
+        let err = io::Error::new(io::ErrorKind::NotFound, "/secret/path");
+        let error = Error::from(err);
+        assert_eq!(StatusCode::NotFound, error.status());
+        assert_eq!(None, error.message());
+        assert_eq!("404 Not Found", error.to_string());
+    }
+}
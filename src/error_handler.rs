@@ -0,0 +1,71 @@
+use crate::{body::Body, mime, response::Response};
+use async_trait::async_trait;
+use std::{fs, future::Future, io, path::Path};
+
+/// Hook to rewrite error responses, e.g. to send a branded `404 Not Found` page. It is set with
+/// `ServerBuilder::error_handler()` or `ServerBuilder::error_page()`.
+///
+/// The hook is called for responses whose status code is 4xx or 5xx and whose body is empty or
+/// made from `qz::Error`, which includes errors of parsing requests, routing and handlers.
+/// Responses with a body built by a handler are sent as they are.
+///
+/// # Examples
+/// ```no_run
+/// use qz::{response::Response, server::Server, status::StatusCode};
+///
+/// async fn render_error(mut response: Response) -> Response {
+///     let code = response.status_code().code();
+///     response.set_content_type(b"text/html; charset=utf-8");
+///     response.set_body(format!("<h1>Oops! ({})</h1>", code));
+///     response
+/// }
+///
+/// let server = Server::builder()
+///     .error_handler(render_error)
+///     .error_page_file(StatusCode::NotFound, "./static/404.html")
+///     .unwrap()
+///     .build();
+/// ```
+#[async_trait]
+pub trait ErrorHandler: Send + Sync + 'static {
+    async fn call(&self, response: Response) -> Response;
+}
+
+#[async_trait]
+impl<F, Fut> ErrorHandler for F
+where
+    F: Send + Sync + 'static + Fn(Response) -> Fut,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    async fn call(&self, response: Response) -> Response {
+        self(response).await
+    }
+}
+
+/// Error page loaded from a file. The file is read once when it is created.
+/// The status code and headers of the error response are kept, so `Allow` of `405 Method Not
+/// Allowed` or `WWW-Authenticate` of `401 Unauthorized` are still sent.
+#[derive(Clone, Debug)]
+pub struct ErrorPage {
+    body: Vec<u8>,
+    mime_type: &'static [u8],
+}
+
+impl ErrorPage {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self {
+            body: fs::read(path)?,
+            mime_type: mime::filename_to_mime(path),
+        })
+    }
+}
+
+#[async_trait]
+impl ErrorHandler for ErrorPage {
+    async fn call(&self, mut response: Response) -> Response {
+        response.set_content_type(self.mime_type);
+        response.set_body(Body::from(self.body.clone()));
+        response
+    }
+}
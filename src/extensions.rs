@@ -0,0 +1,119 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+};
+
+/// Type-keyed map to pass data along with `Request` or `Response`, e.g. a user authenticated by
+/// middleware. It holds at most one value for each type, so wrap a common type like `String`
+/// in a type of your own.
+///
+/// # Examples
+/// ```rust
+/// use qz::Extensions;
+///
+/// #[derive(Debug, PartialEq)]
+/// struct RequestId(u64);
+///
+/// let mut extensions = Extensions::new();
+/// extensions.insert(RequestId(42));
+/// assert_eq!(Some(&RequestId(42)), extensions.get::<RequestId>());
+/// assert_eq!(None, extensions.get::<String>());
+/// ```
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value replacing the old value of the same type, which is returned.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>())?.downcast_ref()
+    }
+
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.map.get_mut(&TypeId::of::<T>())?.downcast_mut()
+    }
+
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Values cannot be compared because their types are erased, so extensions holding values of
+/// the same types are regarded as equal.
+impl PartialEq for Extensions {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.map.keys().all(|key| other.map.contains_key(key))
+    }
+}
+
+impl Eq for Extensions {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct User(String);
+
+    #[test]
+    fn insert_and_get() {
+        let mut extensions = Extensions::new();
+        assert_eq!(None, extensions.insert(User("alice".to_string())));
+        assert_eq!(None, extensions.insert(42u32));
+        assert_eq!(Some(&User("alice".to_string())), extensions.get::<User>());
+        assert_eq!(
+            Some(User("alice".to_string())),
+            extensions.insert(User("bob".to_string()))
+        );
+        *extensions.get_mut::<u32>().unwrap() += 1;
+        assert_eq!(Some(&43), extensions.get::<u32>());
+        assert_eq!(None, extensions.get::<u64>());
+        assert_eq!(2, extensions.len());
+    }
+
+    #[test]
+    fn remove() {
+        let mut extensions = Extensions::new();
+        extensions.insert(User("alice".to_string()));
+        assert!(extensions.contains::<User>());
+        assert_eq!(Some(User("alice".to_string())), extensions.remove::<User>());
+        assert_eq!(None, extensions.remove::<User>());
+        assert!(extensions.is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Extractors to get values from `Request` as arguments of handlers.
+//!
+//! A handler can be an async function taking extractors instead of `Request` and `State`.
+//! When an extractor fails, the handler is not called and the error is sent as the response.
+//!
+//! # Examples
+//! ```no_run
+//! use qz::{
+//!     extract::{Json, Path, State},
+//!     method::Method,
+//!     server::Server,
+//! };
+//! use serde::Deserialize;
+//! use std::sync::Arc;
+//!
+//! #[derive(Deserialize)]
+//! struct Params {
+//!     id: u32,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Comment {
+//!     text: String,
+//! }
+//!
+//! async fn post_comment(
+//!     Path(params): Path<Params>,
+//!     State(prefix): State<Arc<String>>,
+//!     Json(comment): Json<Comment>,
+//! ) -> String {
+//!     format!("{}{}: {}", prefix, params.id, comment.text)
+//! }
+//!
+//! let server = Server::builder_with_state(Arc::new("#".to_string()))
+//!     .route("/posts/:id/comments", Method::Post, post_comment)
+//!     .build();
+//! ```
+
+use crate::{
+    body::Body,
+    header::typed::{ContentType, TypedHeader},
+    mime,
+    request::Request,
+    status::StatusCode,
+    Error,
+};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+
+/// Value which can be created from a request. Return `Err` with 4xx status code if the request is
+/// invalid.
+#[async_trait]
+pub trait FromRequest<S>: Sized
+where
+    S: Clone + Send + Sync + 'static,
+{
+    async fn from_request(request: &Request, state: &S) -> crate::Result<Self>;
+
+    /// Used by `Option<Self>`. Return `Ok(None)` only if the value is absent from the request,
+    /// e.g. a missing header, and `Err` if it is present but invalid.
+    /// The value is never regarded as absent by default.
+    async fn from_request_optional(request: &Request, state: &S) -> crate::Result<Option<Self>> {
+        Self::from_request(request, state).await.map(Some)
+    }
+}
+
+/// Body parsed as JSON. `Content-Type` must be `application/json` or `*/*+json`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+/// Body parsed as `application/x-www-form-urlencoded` data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Form<T>(pub T);
+
+/// Query string of the URI. A request without query string is regarded as empty one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Query<T>(pub T);
+
+/// Named parameters and wildcards in the route, deserialized as fields of a struct.
+/// e.g. `Path<Params>` where `Params` has `id: u32` for the route `/users/:id`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Path<T>(pub T);
+
+/// State of the server.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct State<S>(pub S);
+
+/// Typed header, e.g. `Header<Authorization>`. Use `Option<Header<H>>` for an optional header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header<H>(pub H);
+
+fn has_content_type(request: &Request, expected: &[u8]) -> bool {
+    let content_type = match request.typed_header::<ContentType>() {
+        Some(content_type) => content_type,
+        None => return false,
+    };
+    let mime_type = content_type.mime_type().as_bytes();
+    if mime_type.eq_ignore_ascii_case(expected) {
+        return true;
+    }
+    // Structured syntax suffix like `application/problem+json`.
+    expected == mime::APPLICATION_JSON && mime_type.to_ascii_lowercase().ends_with(b"+json")
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Json<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        if !has_content_type(request, mime::APPLICATION_JSON) {
+            return Err(Error::new(
+                StatusCode::UnsupportedMediaType,
+                "expected `Content-Type: application/json`",
+            ));
+        }
+        request.body().into_json().map(Json)
+    }
+
+    async fn from_request_optional(request: &Request, state: &S) -> crate::Result<Option<Self>> {
+        if request.body().is_none() {
+            return Ok(None);
+        }
+        Self::from_request(request, state).await.map(Some)
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Form<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        if !has_content_type(request, mime::APPLICATION_WWW_FORM) {
+            return Err(Error::new(
+                StatusCode::UnsupportedMediaType,
+                "expected `Content-Type: application/x-www-form-urlencoded`",
+            ));
+        }
+        match request.body() {
+            // An empty form is valid.
+            Body::None => Ok(Form(serde_urlencoded::from_bytes(b"")?)),
+            body => body.into_form().map(Form),
+        }
+    }
+
+    async fn from_request_optional(request: &Request, state: &S) -> crate::Result<Option<Self>> {
+        if request.body().is_none() {
+            return Ok(None);
+        }
+        Self::from_request(request, state).await.map(Some)
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Query<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        request.query().map(Query)
+    }
+}
+
+#[async_trait]
+impl<S, T> FromRequest<S> for Path<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: DeserializeOwned,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        // Parameters are strings, so deserialize them like a query string to parse numbers.
+        let params = serde_urlencoded::to_string(request.params()).map_err(|err| {
+            Error::new(
+                StatusCode::InternalServerError,
+                "failed to encode path parameters",
+            )
+            .set_source(err)
+        })?;
+        Ok(Path(serde_urlencoded::from_str(&params)?))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for State<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    async fn from_request(_request: &Request, state: &S) -> crate::Result<Self> {
+        Ok(State(state.clone()))
+    }
+}
+
+#[async_trait]
+impl<S, H> FromRequest<S> for Header<H>
+where
+    S: Clone + Send + Sync + 'static,
+    H: TypedHeader,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        request.typed_header().map(Header).ok_or_else(|| {
+            let message = format!("missing or invalid `{}` header", H::name());
+            Error::new(StatusCode::BadRequest, message)
+        })
+    }
+
+    async fn from_request_optional(request: &Request, state: &S) -> crate::Result<Option<Self>> {
+        if !request.headers().contains_key(&H::name()) {
+            return Ok(None);
+        }
+        Self::from_request(request, state).await.map(Some)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for Body
+where
+    S: Clone + Send + Sync + 'static,
+{
+    async fn from_request(request: &Request, _state: &S) -> crate::Result<Self> {
+        Ok(request.body().clone())
+    }
+}
+
+/// `None` if the value is absent from the request, e.g. `Option<Json<T>>` for a request without a
+/// body. A value which is present but invalid is still rejected.
+#[async_trait]
+impl<S, T> FromRequest<S> for Option<T>
+where
+    S: Clone + Send + Sync + 'static,
+    T: FromRequest<S> + Send,
+{
+    async fn from_request(request: &Request, state: &S) -> crate::Result<Self> {
+        T::from_request_optional(request, state).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{header::typed::Authorization, header::HeaderName};
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct User {
+        name: String,
+        age: u32,
+    }
+
+    async fn extract<T: FromRequest<()>>(request: &Request) -> crate::Result<T> {
+        T::from_request(request, &()).await
+    }
+
+    #[tokio::test]
+    async fn extract_json() {
+        let request = Request::builder()
+            .set_header(HeaderName::ContentType, "application/json; charset=utf-8")
+            .set_body(r#"{"name": "alice", "age": 20}"#)
+            .build();
+        let Json(user) = extract::<Json<User>>(&request).await.unwrap();
+        assert_eq!("alice", user.name);
+
+        let request = Request::builder()
+            .set_body(r#"{"name": "alice", "age": 20}"#)
+            .build();
+        assert_eq!(
+            StatusCode::UnsupportedMediaType,
+            extract::<Json<User>>(&request).await.unwrap_err().status()
+        );
+
+        let request = Request::builder()
+            .set_header(HeaderName::ContentType, "application/merge-patch+json")
+            .set_body(r#"{"name": "alice"}"#)
+            .build();
+        assert_eq!(
+            StatusCode::BadRequest,
+            extract::<Json<User>>(&request).await.unwrap_err().status()
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_optional_json() {
+        let request = Request::default();
+        assert_eq!(None, extract::<Option<Json<User>>>(&request).await.unwrap());
+
+        let request = Request::builder().set_body(r#"{"name": "alice"}"#).build();
+        assert_eq!(
+            StatusCode::UnsupportedMediaType,
+            extract::<Option<Json<User>>>(&request)
+                .await
+                .unwrap_err()
+                .status()
+        );
+
+        let request = Request::builder()
+            .set_header(HeaderName::ContentType, "application/json")
+            .set_body(r#"{"name": "#)
+            .build();
+        assert_eq!(
+            StatusCode::BadRequest,
+            extract::<Option<Json<User>>>(&request)
+                .await
+                .unwrap_err()
+                .status()
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_form() {
+        let request = Request::builder()
+            .set_header(HeaderName::ContentType, "application/x-www-form-urlencoded")
+            .set_body("name=alice&age=20")
+            .build();
+        let Form(user) = extract::<Form<User>>(&request).await.unwrap();
+        assert_eq!(20, user.age);
+
+        let request = Request::builder()
+            .set_header(HeaderName::ContentType, "application/x-www-form-urlencoded")
+            .set_body("name=alice&age=twenty")
+            .build();
+        assert_eq!(
+            StatusCode::BadRequest,
+            extract::<Form<User>>(&request).await.unwrap_err().status()
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_query_and_path() {
+        let mut request = Request::builder().set_uri("/users?name=bob&age=30").build();
+        let Query(user) = extract::<Query<User>>(&request).await.unwrap();
+        assert_eq!("bob", user.name);
+
+        let mut params = HashMap::new();
+        params.insert("name".to_string(), "carol".to_string());
+        params.insert("age".to_string(), "40".to_string());
+        request.params = params;
+        let Path(user) = extract::<Path<User>>(&request).await.unwrap();
+        assert_eq!(40, user.age);
+
+        request
+            .params
+            .insert("age".to_string(), "forty".to_string());
+        assert_eq!(
+            StatusCode::BadRequest,
+            extract::<Path<User>>(&request).await.unwrap_err().status()
+        );
+    }
+
+    #[tokio::test]
+    async fn extract_header() {
+        let request = Request::builder()
+            .set_header(HeaderName::Authorization, "Bearer token")
+            .build();
+        let Header(authorization) = extract::<Header<Authorization>>(&request).await.unwrap();
+        assert_eq!(Some("token"), authorization.as_bearer());
+
+        let request = Request::default();
+        let error = extract::<Header<Authorization>>(&request)
+            .await
+            .unwrap_err();
+        assert_eq!(StatusCode::BadRequest, error.status());
+        assert_eq!(
+            Some("missing or invalid `Authorization` header"),
+            error.message()
+        );
+        assert_eq!(
+            Ok(None),
+            extract::<Option<Header<Authorization>>>(&request).await
+        );
+
+        let request = Request::builder()
+            .set_header(HeaderName::Authorization, "(Bearer) token")
+            .build();
+        assert_eq!(
+            StatusCode::BadRequest,
+            extract::<Option<Header<Authorization>>>(&request)
+                .await
+                .unwrap_err()
+                .status()
+        );
+    }
+}
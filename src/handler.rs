@@ -1,6 +1,10 @@
-use crate::{request::Request, response::Response};
+use crate::{
+    extract::FromRequest,
+    request::Request,
+    response::{IntoResponse, Response},
+};
 use async_trait::async_trait;
-use std::{fmt, future::Future};
+use std::{fmt, future::Future, marker::PhantomData};
 
 /// Abstruction over all process to create response from request.
 ///
@@ -19,13 +23,97 @@ where
     State: Clone + Send + Sync + 'static,
     F: Send + Sync + 'static + Fn(Request, State) -> Fut,
     Fut: Future + Send + 'static,
-    Fut::Output: Into<Response>,
+    Fut::Output: IntoResponse,
 {
     async fn call(&self, request: Request, state: State) -> crate::Result<Response> {
-        Ok(self(request, state).await.into())
+        Ok(self(request, state).await.into_response())
     }
 }
 
+/// Conversion into `Handler`, which is accepted by `ServerBuilder::route()`. This is implemented
+/// for `Handler`s and for async functions taking up to 8 extractors (see `qz::extract`).
+///
+/// `Args` only distinguishes the implementations, so closures passed to `route()` must have
+/// annotated arguments, e.g. `|_: Request, _: ()| async { "hello" }`.
+pub trait IntoHandler<State, Args>
+where
+    State: Clone + Send + Sync + 'static,
+{
+    type Handler: Handler<State>;
+
+    fn into_handler(self) -> Self::Handler;
+}
+
+/// `Args` of `IntoHandler` for `Handler`s.
+#[doc(hidden)]
+pub enum Raw {}
+
+impl<State, H> IntoHandler<State, Raw> for H
+where
+    State: Clone + Send + Sync + 'static,
+    H: Handler<State>,
+{
+    type Handler = H;
+
+    fn into_handler(self) -> Self::Handler {
+        self
+    }
+}
+
+/// `Handler` which calls `F` with values extracted from the request.
+pub struct ExtractorHandler<F, Args> {
+    f: F,
+    _args: PhantomData<fn() -> Args>,
+}
+
+macro_rules! impl_extractor_handler {
+    ($($T:ident),*) => {
+        #[async_trait]
+        impl<State, F, Fut, $($T,)*> Handler<State> for ExtractorHandler<F, ($($T,)*)>
+        where
+            State: Clone + Send + Sync + 'static,
+            F: Send + Sync + 'static + Fn($($T,)*) -> Fut,
+            Fut: Future + Send + 'static,
+            Fut::Output: IntoResponse,
+            $($T: FromRequest<State> + Send + 'static,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            async fn call(&self, request: Request, state: State) -> crate::Result<Response> {
+                $(let $T = $T::from_request(&request, &state).await?;)*
+                Ok((self.f)($($T,)*).await.into_response())
+            }
+        }
+
+        impl<State, F, Fut, $($T,)*> IntoHandler<State, ($($T,)*)> for F
+        where
+            State: Clone + Send + Sync + 'static,
+            F: Send + Sync + 'static + Fn($($T,)*) -> Fut,
+            Fut: Future + Send + 'static,
+            Fut::Output: IntoResponse,
+            $($T: FromRequest<State> + Send + 'static,)*
+        {
+            type Handler = ExtractorHandler<F, ($($T,)*)>;
+
+            fn into_handler(self) -> Self::Handler {
+                ExtractorHandler {
+                    f: self,
+                    _args: PhantomData,
+                }
+            }
+        }
+    };
+}
+
+impl_extractor_handler!();
+impl_extractor_handler!(T1);
+impl_extractor_handler!(T1, T2);
+impl_extractor_handler!(T1, T2, T3);
+impl_extractor_handler!(T1, T2, T3, T4);
+impl_extractor_handler!(T1, T2, T3, T4, T5);
+impl_extractor_handler!(T1, T2, T3, T4, T5, T6);
+impl_extractor_handler!(T1, T2, T3, T4, T5, T6, T7);
+impl_extractor_handler!(T1, T2, T3, T4, T5, T6, T7, T8);
+
 impl<State> fmt::Debug for dyn Handler<State>
 where
     State: Clone + Send + Sync + 'static,
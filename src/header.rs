@@ -1,40 +1,86 @@
-use std::{convert::From, fmt};
+mod map;
+pub mod typed;
+
+use crate::{parser::is_tchar, status::StatusCode};
+pub use map::{HeaderMap, Iter};
+use std::{
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
 pub type HeaderValue = Vec<u8>;
 
 macro_rules! define_headers {
     ($(($name:ident, $upper_str:expr, $lower_str:expr),)+) => {
+        /// Name of a header. Standard headers have their own variants and others are represented
+        /// as `Custom`, e.g. `X-Request-Id`, which is created by `HeaderName::try_from()`.
         #[derive(Clone, Debug, Hash, PartialEq, Eq)]
         pub enum HeaderName {
             $($name,)+
-            Unknown,
+            Custom(CustomHeaderName),
         }
 
         impl AsRef<[u8]> for HeaderName {
             fn as_ref(&self) -> &[u8] {
                 match self {
                     $(HeaderName::$name => $upper_str,)+
-                    HeaderName::Unknown => b"Unknown",
+                    HeaderName::Custom(name) => &name.0,
                 }
             }
         }
 
-        impl From<Vec<u8>> for HeaderName {
-            fn from(mut name: Vec<u8>) -> Self {
+        /// A name must be a token, so that it cannot break the header section when it is sent.
+        impl TryFrom<Vec<u8>> for HeaderName {
+            type Error = StatusCode;
+            fn try_from(name: Vec<u8>) -> Result<Self, Self::Error> {
+                if name.is_empty() || !name.iter().all(|&b| is_tchar(b)) {
+                    return Err(StatusCode::BadRequest);
+                }
                 // RFC2616 says a header name is case insensitive.
                 // https://www.w3.org/Protocols/rfc2616/rfc2616-sec4.html#sec4.2
-                name.make_ascii_lowercase();
-                match &name[..] {
+                let header_name = match &name.to_ascii_lowercase()[..] {
                     $($lower_str => HeaderName::$name,)+
-                    _ => HeaderName::Unknown
-                }
+                    _ => HeaderName::Custom(CustomHeaderName(name)),
+                };
+                Ok(header_name)
             }
         }
     }
 }
 
+/// Name of a header which is not defined as a variant of `HeaderName`.
+/// It is sent as it is spelled, but names different only in case are equal.
+#[derive(Clone, Debug)]
+pub struct CustomHeaderName(Vec<u8>);
+
+impl PartialEq for CustomHeaderName {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for CustomHeaderName {}
+
+impl Hash for CustomHeaderName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in &self.0 {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+    }
+}
+
 define_headers!(
     (Accept, b"Accept", b"accept"),
+    (AcceptCharset, b"Accept-Charset", b"accept-charset"),
+    (AcceptEncoding, b"Accept-Encoding", b"accept-encoding"),
+    (AcceptLanguage, b"Accept-Language", b"accept-language"),
+    (AcceptRanges, b"Accept-Ranges", b"accept-ranges"),
+    (
+        AccessControlAllowCredentials,
+        b"Access-Control-Allow-Credentials",
+        b"access-control-allow-credentials"
+    ),
     (
         AccessControlAllowHeaders,
         b"Access-Control-Allow-Headers",
@@ -50,24 +96,128 @@ define_headers!(
         b"Access-Control-Allow-Origin",
         b"access-control-allow-origin"
     ),
+    (
+        AccessControlExposeHeaders,
+        b"Access-Control-Expose-Headers",
+        b"access-control-expose-headers"
+    ),
     (
         AccessControlAllowMaxAge,
         b"Access-Control-Max-Age",
         b"access-control-max-age"
     ),
+    (
+        AccessControlRequestHeaders,
+        b"Access-Control-Request-Headers",
+        b"access-control-request-headers"
+    ),
+    (
+        AccessControlRequestMethod,
+        b"Access-Control-Request-Method",
+        b"access-control-request-method"
+    ),
+    (Age, b"Age", b"age"),
+    (Allow, b"Allow", b"allow"),
     (Authorization, b"Authorization", b"authorization"),
+    (CacheControl, b"Cache-Control", b"cache-control"),
+    (Connection, b"Connection", b"connection"),
+    (
+        ContentDisposition,
+        b"Content-Disposition",
+        b"content-disposition"
+    ),
+    (ContentEncoding, b"Content-Encoding", b"content-encoding"),
+    (ContentLanguage, b"Content-Language", b"content-language"),
     (ContentLength, b"Content-Length", b"content-length"),
+    (ContentLocation, b"Content-Location", b"content-location"),
+    (ContentRange, b"Content-Range", b"content-range"),
+    (
+        ContentSecurityPolicy,
+        b"Content-Security-Policy",
+        b"content-security-policy"
+    ),
     (ContentType, b"Content-Type", b"content-type"),
+    (Cookie, b"Cookie", b"cookie"),
+    (Date, b"Date", b"date"),
+    (ETag, b"ETag", b"etag"),
+    (Expect, b"Expect", b"expect"),
+    (Expires, b"Expires", b"expires"),
+    (Forwarded, b"Forwarded", b"forwarded"),
+    (From, b"From", b"from"),
     (Host, b"Host", b"host"),
+    (IfMatch, b"If-Match", b"if-match"),
+    (IfModifiedSince, b"If-Modified-Since", b"if-modified-since"),
+    (IfNoneMatch, b"If-None-Match", b"if-none-match"),
+    (IfRange, b"If-Range", b"if-range"),
+    (
+        IfUnmodifiedSince,
+        b"If-Unmodified-Since",
+        b"if-unmodified-since"
+    ),
+    (KeepAlive, b"Keep-Alive", b"keep-alive"),
+    (LastModified, b"Last-Modified", b"last-modified"),
+    (Link, b"Link", b"link"),
     (Location, b"Location", b"location"),
+    (MaxForwards, b"Max-Forwards", b"max-forwards"),
     (Origin, b"Origin", b"origin"),
+    (Pragma, b"Pragma", b"pragma"),
+    (
+        ProxyAuthenticate,
+        b"Proxy-Authenticate",
+        b"proxy-authenticate"
+    ),
+    (
+        ProxyAuthorization,
+        b"Proxy-Authorization",
+        b"proxy-authorization"
+    ),
+    (Range, b"Range", b"range"),
+    (Referer, b"Referer", b"referer"),
+    (ReferrerPolicy, b"Referrer-Policy", b"referrer-policy"),
+    (RetryAfter, b"Retry-After", b"retry-after"),
+    (Server, b"Server", b"server"),
+    (SetCookie, b"Set-Cookie", b"set-cookie"),
+    (
+        StrictTransportSecurity,
+        b"Strict-Transport-Security",
+        b"strict-transport-security"
+    ),
+    (Te, b"TE", b"te"),
+    (Trailer, b"Trailer", b"trailer"),
+    (TransferEncoding, b"Transfer-Encoding", b"transfer-encoding"),
+    (Upgrade, b"Upgrade", b"upgrade"),
     (UserAgent, b"User-Agent", b"user-agent"),
+    (Vary, b"Vary", b"vary"),
+    (Via, b"Via", b"via"),
     (WwwAuthenticate, b"WWW-Authenticate", b"www-authenticate"),
+    (
+        XContentTypeOptions,
+        b"X-Content-Type-Options",
+        b"x-content-type-options"
+    ),
+    (XForwardedFor, b"X-Forwarded-For", b"x-forwarded-for"),
+    (XForwardedHost, b"X-Forwarded-Host", b"x-forwarded-host"),
+    (XForwardedProto, b"X-Forwarded-Proto", b"x-forwarded-proto"),
+    (XFrameOptions, b"X-Frame-Options", b"x-frame-options"),
 );
 
+impl TryFrom<&str> for HeaderName {
+    type Error = StatusCode;
+    fn try_from(name: &str) -> Result<Self, Self::Error> {
+        Self::try_from(name.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<String> for HeaderName {
+    type Error = StatusCode;
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::try_from(name.into_bytes())
+    }
+}
+
 impl fmt::Display for HeaderName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", std::str::from_utf8(self.as_ref()).unwrap())
+        write!(f, "{}", String::from_utf8_lossy(self.as_ref()))
     }
 }
 
@@ -77,6 +227,29 @@ mod tests {
 
     #[test]
     fn header_name_case_insensitive() {
-        assert_eq!(HeaderName::Accept, HeaderName::from(b"accept".to_vec()));
+        assert_eq!(
+            Ok(HeaderName::Accept),
+            HeaderName::try_from(b"accept".to_vec())
+        );
+    }
+
+    #[test]
+    fn custom_header_name() {
+        let name = HeaderName::try_from("X-Request-Id").unwrap();
+        assert_eq!(Ok(&name), HeaderName::try_from("x-request-id").as_ref());
+        assert_ne!(Ok(&name), HeaderName::try_from("X-Trace-Id").as_ref());
+        assert_eq!(b"X-Request-Id", name.as_ref());
+
+        let mut map = HeaderMap::new();
+        map.append(name, "42");
+        let lower = HeaderName::try_from("x-request-id").unwrap();
+        assert_eq!(Some(&b"42".to_vec()), map.get(&lower));
+    }
+
+    #[test]
+    fn invalid_header_name() {
+        for name in ["", "X-Evil\r\nSet-Cookie", "X-A: b", "X A"] {
+            assert_eq!(Err(StatusCode::BadRequest), HeaderName::try_from(name));
+        }
     }
 }
@@ -0,0 +1,208 @@
+use super::{typed::TypedHeader, HeaderName, HeaderValue};
+use std::{iter::FromIterator, slice};
+
+/// Collection of headers. Unlike `HashMap`, a name can have multiple values, e.g. `Set-Cookie`,
+/// and headers are kept in the order they are added, so they are sent in the same order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    entries: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of values, not of names.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, name: &HeaderName) -> bool {
+        self.entries.iter().any(|(n, _)| n == name)
+    }
+
+    /// First value of `name`.
+    pub fn get(&self, name: &HeaderName) -> Option<&HeaderValue> {
+        self.entries
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// All values of `name` in the order they are added.
+    pub fn get_all<'a>(&'a self, name: &'a HeaderName) -> impl Iterator<Item = &'a HeaderValue> {
+        self.entries
+            .iter()
+            .filter(move |(n, _)| n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Set `value` to `name` replacing all existing values of it. The value takes the place of
+    /// the first existing one. The first of replaced values is returned.
+    pub fn insert(
+        &mut self,
+        name: HeaderName,
+        value: impl Into<HeaderValue>,
+    ) -> Option<HeaderValue> {
+        let value = value.into();
+        match self.entries.iter().position(|(n, _)| *n == name) {
+            Some(index) => {
+                let old = std::mem::replace(&mut self.entries[index].1, value);
+                let mut i = 0;
+                self.entries.retain(|(n, _)| {
+                    let keep = i <= index || *n != name;
+                    i += 1;
+                    keep
+                });
+                Some(old)
+            }
+            None => {
+                self.entries.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Add `value` to `name` keeping existing values.
+    pub fn append(&mut self, name: HeaderName, value: impl Into<HeaderValue>) {
+        self.entries.push((name, value.into()));
+    }
+
+    /// Remove all values of `name` and return the first of them.
+    pub fn remove(&mut self, name: &HeaderName) -> Option<HeaderValue> {
+        let index = self.entries.iter().position(|(n, _)| n == name)?;
+        let (_, removed) = self.entries.remove(index);
+        self.entries.retain(|(n, _)| n != name);
+        Some(removed)
+    }
+
+    /// Parse the header as `H`. `None` is returned if the header is missing or malformed.
+    pub fn typed_get<H: TypedHeader>(&self) -> Option<H> {
+        let name = H::name();
+        let mut values = self.get_all(&name);
+        let first = values.next()?;
+        let mut joined = None;
+        for value in values {
+            let joined = joined.get_or_insert_with(|| first.clone());
+            joined.extend_from_slice(H::SEPARATOR);
+            joined.extend_from_slice(value);
+        }
+        H::decode(joined.as_ref().unwrap_or(first)).ok()
+    }
+
+    /// Set the header replacing existing values of it.
+    pub fn typed_insert<H: TypedHeader>(&mut self, header: &H) {
+        self.insert(H::name(), header.encode());
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+}
+
+/// Iterator over pairs of a name and a value in `HeaderMap`.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    inner: slice::Iter<'a, (HeaderName, HeaderValue)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a HeaderName, &'a HeaderValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(name, value)| (name, value))
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderMap {
+    type Item = (&'a HeaderName, &'a HeaderValue);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<(HeaderName, HeaderValue)> for HeaderMap {
+    fn from_iter<I: IntoIterator<Item = (HeaderName, HeaderValue)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (name, value) in iter {
+            map.append(name, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::header::typed;
+
+    fn map() -> HeaderMap {
+        let mut map = HeaderMap::new();
+        map.append(HeaderName::Accept, "text/html");
+        map.append(HeaderName::Host, "localhost");
+        map.append(HeaderName::Accept, "application/json");
+        map
+    }
+
+    #[test]
+    fn get_all() {
+        let map = map();
+        assert_eq!(Some(&b"text/html".to_vec()), map.get(&HeaderName::Accept));
+        assert_eq!(
+            vec![&b"text/html".to_vec(), &b"application/json".to_vec()],
+            map.get_all(&HeaderName::Accept).collect::<Vec<_>>()
+        );
+        assert_eq!(0, map.get_all(&HeaderName::Range).count());
+    }
+
+    #[test]
+    fn insert_replaces_all_values() {
+        let mut map = map();
+        assert_eq!(
+            Some(b"text/html".to_vec()),
+            map.insert(HeaderName::Accept, "*/*")
+        );
+        let names = map.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+        assert_eq!(vec![HeaderName::Accept, HeaderName::Host], names);
+        assert_eq!(Some(&b"*/*".to_vec()), map.get(&HeaderName::Accept));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = map();
+        assert_eq!(Some(b"text/html".to_vec()), map.remove(&HeaderName::Accept));
+        assert_eq!(1, map.len());
+        assert!(!map.contains_key(&HeaderName::Accept));
+        assert_eq!(None, map.remove(&HeaderName::Accept));
+    }
+
+    #[test]
+    fn keep_order() {
+        let map = map();
+        let names = map.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            vec![HeaderName::Accept, HeaderName::Host, HeaderName::Accept],
+            names
+        );
+    }
+
+    #[test]
+    fn typed_get_cookies_in_multiple_lines() {
+        let mut map = HeaderMap::new();
+        map.append(HeaderName::Cookie, "a=1,2");
+        map.append(HeaderName::Cookie, "b=3");
+        let cookie = map.typed_get::<typed::Cookie>().unwrap();
+        assert_eq!(Some("1,2"), cookie.get("a"));
+        assert_eq!(Some("3"), cookie.get("b"));
+    }
+}
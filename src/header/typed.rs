@@ -0,0 +1,157 @@
+mod accept;
+mod authorization;
+mod cache_control;
+mod content_type;
+mod cookie;
+mod date;
+mod etag;
+mod range;
+
+pub use accept::{Accept, MediaRange};
+pub use authorization::Authorization;
+pub use cache_control::CacheControl;
+pub use content_type::ContentType;
+pub use cookie::Cookie;
+pub use date::{Date, Expires, IfModifiedSince, IfUnmodifiedSince, LastModified};
+pub use etag::{ETag, EntityTag, IfNoneMatch, IfRange};
+pub use range::{ByteRangeSpec, Range};
+
+use super::{HeaderName, HeaderValue};
+use crate::{parser::is_tchar, status::StatusCode};
+use std::str;
+
+/// Header which is parsed from and encoded into a raw value, so that handlers and middlewares do
+/// not have to split bytes by hand.
+///
+/// # Examples
+/// ```rust
+/// use qz::{header::typed::ContentType, request::Request, response::Response};
+///
+/// let request = Request::builder()
+///     .typed_header(ContentType::new("text/html").with_param("charset", "utf-8"))
+///     .build();
+/// let content_type = request.typed_header::<ContentType>().unwrap();
+/// assert_eq!("text/html", content_type.mime_type());
+/// assert_eq!(Some("utf-8"), content_type.charset());
+/// ```
+pub trait TypedHeader: Sized {
+    /// Separator to join values of the header which appears multiple times.
+    const SEPARATOR: &'static [u8] = b", ";
+
+    fn name() -> HeaderName;
+
+    /// Parse a value of the header. If the header appears multiple times, the values are joined
+    /// with `SEPARATOR` before parsing.
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode>;
+
+    fn encode(&self) -> HeaderValue;
+}
+
+pub(crate) fn to_str(value: &[u8]) -> crate::Result<&str, StatusCode> {
+    str::from_utf8(value).or(Err(StatusCode::BadRequest))
+}
+
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_tchar)
+}
+
+/// Split `value` at `separator` which is not in quoted strings. Elements are trimmed and empty
+/// ones are skipped, e.g. `a, "b,c",,d` is split into `a`, `"b,c"` and `d`.
+pub(crate) fn split_quoted(value: &str, separator: char) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut in_quote = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_quote => escaped = true,
+            '"' => in_quote = !in_quote,
+            c if c == separator && !in_quote => {
+                elements.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+    elements.push(&value[start..]);
+    elements
+        .into_iter()
+        .map(str::trim)
+        .filter(|element| !element.is_empty())
+        .collect()
+}
+
+/// Parse a token or a quoted string into its content.
+pub(crate) fn unquote(value: &str) -> crate::Result<String, StatusCode> {
+    let quoted = match value.strip_prefix('"') {
+        Some(quoted) => quoted.strip_suffix('"').ok_or(StatusCode::BadRequest)?,
+        None if is_token(value) => return Ok(value.to_string()),
+        None => return Err(StatusCode::BadRequest),
+    };
+    let mut unquoted = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next().ok_or(StatusCode::BadRequest)?),
+            '"' => return Err(StatusCode::BadRequest),
+            c => unquoted.push(c),
+        }
+    }
+    Ok(unquoted)
+}
+
+/// Make a token or a quoted string from `value`.
+pub(crate) fn quote(value: &str) -> String {
+    if is_token(value) {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Parse parameters such as `charset=utf-8`. Names are case-insensitive, so they are lowercased.
+pub(crate) fn parse_params<'a>(
+    params: impl Iterator<Item = &'a str>,
+) -> crate::Result<Vec<(String, String)>, StatusCode> {
+    params
+        .map(|param| {
+            let (name, value) = param.split_once('=').ok_or(StatusCode::BadRequest)?;
+            let name = name.trim();
+            if !is_token(name) {
+                return Err(StatusCode::BadRequest);
+            }
+            Ok((name.to_ascii_lowercase(), unquote(value.trim())?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_list() {
+        assert_eq!(
+            vec!["a", "\"b,c\"", "d"],
+            split_quoted("a, \"b,c\",,d", ',')
+        );
+        assert_eq!(
+            vec!["a=\"x\\\";y\"", "b"],
+            split_quoted("a=\"x\\\";y\"; b", ';')
+        );
+    }
+
+    #[test]
+    fn quote_and_unquote() {
+        assert_eq!(Ok("utf-8".to_string()), unquote("utf-8"));
+        assert_eq!(Ok("a \"b\"".to_string()), unquote("\"a \\\"b\\\"\""));
+        assert_eq!(Err(StatusCode::BadRequest), unquote("\"a"));
+        assert_eq!(Err(StatusCode::BadRequest), unquote("a b"));
+        assert_eq!("utf-8", quote("utf-8"));
+        assert_eq!("\"a \\\"b\\\"\"", quote("a \"b\""));
+    }
+}
@@ -0,0 +1,216 @@
+use super::{
+    content_type::is_mime_type, parse_params, quote, split_quoted, to_str, ContentType, TypedHeader,
+};
+use crate::{
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// Media range in `Accept` header with its quality, e.g. `text/*;q=0.8`.
+/// Quality is represented in thousandths, so `q=0.8` is 800.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaRange {
+    mime_type: String,
+    params: Vec<(String, String)>,
+    quality: u16,
+}
+
+impl MediaRange {
+    pub fn new(mime_type: impl Into<String>, quality: u16) -> Self {
+        Self {
+            mime_type: mime_type.into().to_ascii_lowercase(),
+            params: Vec::new(),
+            quality: quality.min(1000),
+        }
+    }
+
+    /// Media type possibly with wildcards, e.g. `text/*` or `*/*`.
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    pub fn quality(&self) -> u16 {
+        self.quality
+    }
+
+    /// How specific this range is if it matches `content_type`, otherwise `None`.
+    /// `text/html;level=1` is more specific than `text/html`, which is more specific than
+    /// `text/*` and `*/*`.
+    fn specificity(&self, content_type: &ContentType) -> Option<(u8, usize)> {
+        let (ty, subtype) = self.mime_type.split_once('/')?;
+        let (other_ty, other_subtype) = content_type.mime_type().split_once('/')?;
+        let specificity = match (ty, subtype) {
+            ("*", "*") => 0,
+            (ty, "*") if ty == other_ty => 1,
+            (ty, subtype) if ty == other_ty && subtype == other_subtype => 2,
+            _ => return None,
+        };
+        let params_match = self
+            .params
+            .iter()
+            .all(|(name, value)| content_type.param(name) == Some(value.as_str()));
+        if params_match {
+            Some((specificity, self.params.len()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse a quality value, e.g. `0.8`.
+fn parse_quality(value: &str) -> crate::Result<u16, StatusCode> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(StatusCode::BadRequest);
+    }
+    let fraction = format!("{:0<3}", fraction).parse::<u16>().unwrap();
+    match integer {
+        "0" => Ok(fraction),
+        "1" if fraction == 0 => Ok(1000),
+        _ => Err(StatusCode::BadRequest),
+    }
+}
+
+/// `Accept` header, e.g. `text/html, application/json;q=0.9, */*;q=0.1`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Accept {
+    media_ranges: Vec<MediaRange>,
+}
+
+impl Accept {
+    pub fn new(media_ranges: Vec<MediaRange>) -> Self {
+        Self { media_ranges }
+    }
+
+    pub fn media_ranges(&self) -> &[MediaRange] {
+        &self.media_ranges
+    }
+
+    /// Quality of `mime_type` given by the most specific media range matching it.
+    /// 0 is returned if no media range matches, which means it is not acceptable.
+    pub fn quality(&self, mime_type: &str) -> u16 {
+        let content_type = match ContentType::decode(mime_type.as_bytes()) {
+            Ok(content_type) => content_type,
+            Err(_) => return 0,
+        };
+        self.media_ranges
+            .iter()
+            .filter_map(|range| Some((range.specificity(&content_type)?, range.quality)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0, |(_, quality)| quality)
+    }
+
+    /// Choose the most preferred one from `available` media types.
+    /// The former is chosen if some have the same quality.
+    pub fn negotiate<'a>(&self, available: &[&'a str]) -> Option<&'a str> {
+        let mut best: Option<(&str, u16)> = None;
+        for &mime_type in available {
+            let quality = self.quality(mime_type);
+            if quality > 0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((mime_type, quality));
+            }
+        }
+        best.map(|(mime_type, _)| mime_type)
+    }
+}
+
+impl TypedHeader for Accept {
+    fn name() -> HeaderName {
+        HeaderName::Accept
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let media_ranges = split_quoted(to_str(value)?, ',')
+            .into_iter()
+            .map(|range| {
+                let elements = split_quoted(range, ';');
+                let (mime_type, params) = elements.split_first().ok_or(StatusCode::BadRequest)?;
+                if !is_mime_type(mime_type) {
+                    return Err(StatusCode::BadRequest);
+                }
+                let mut media_range = MediaRange::new(*mime_type, 1000);
+                let params = parse_params(params.iter().copied())?;
+                // Parameters after `q` are extensions of `Accept`, not of the media type.
+                match params.iter().position(|(name, _)| name == "q") {
+                    Some(q) => {
+                        media_range.quality = parse_quality(&params[q].1)?;
+                        media_range.params = params[..q].to_vec();
+                    }
+                    None => media_range.params = params,
+                }
+                Ok(media_range)
+            })
+            .collect::<crate::Result<_, StatusCode>>()?;
+        Ok(Self { media_ranges })
+    }
+
+    fn encode(&self) -> HeaderValue {
+        self.media_ranges
+            .iter()
+            .map(|range| {
+                let mut value = range.mime_type.clone();
+                for (name, param) in &range.params {
+                    value.push_str(&format!(";{}={}", name, quote(param)));
+                }
+                if range.quality < 1000 {
+                    let quality = format!("{:03}", range.quality);
+                    value.push_str(&format!(";q=0.{}", quality.trim_end_matches('0')));
+                }
+                value
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+            .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_accept() {
+        let accept = Accept::decode(b"text/html;level=1, text/*;q=0.5, */*;q=0").unwrap();
+        assert_eq!(
+            vec![("level".to_string(), "1".to_string())],
+            accept.media_ranges()[0].params()
+        );
+        assert_eq!(500, accept.media_ranges()[1].quality());
+        assert_eq!(0, accept.media_ranges()[2].quality());
+        assert!(Accept::decode(b"text/html;q=1.5").is_err());
+        assert!(Accept::decode(b"html").is_err());
+    }
+
+    #[test]
+    fn quality() {
+        let accept =
+            Accept::decode(b"text/*;q=0.3, text/html;q=0.7, text/html;level=1, */*;q=0.5").unwrap();
+        assert_eq!(1000, accept.quality("text/html;level=1"));
+        assert_eq!(700, accept.quality("text/html"));
+        assert_eq!(300, accept.quality("text/plain"));
+        assert_eq!(500, accept.quality("image/jpeg"));
+    }
+
+    #[test]
+    fn negotiate() {
+        let accept = Accept::decode(b"application/json;q=0.9, text/plain").unwrap();
+        assert_eq!(
+            Some("text/plain"),
+            accept.negotiate(&["application/json", "text/plain"])
+        );
+        assert_eq!(None, accept.negotiate(&["text/html"]));
+    }
+
+    #[test]
+    fn encode_accept() {
+        let accept = Accept::new(vec![
+            MediaRange::new("text/html", 1000),
+            MediaRange::new("*/*", 800),
+        ]);
+        assert_eq!(b"text/html, */*;q=0.8".to_vec(), accept.encode());
+    }
+}
@@ -0,0 +1,108 @@
+use super::{is_token, to_str, TypedHeader};
+use crate::{
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// `Authorization` header, e.g. `Basic dXNlcjpwYXNz` or `Bearer mF_9.B5f-4.1JqM`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Authorization {
+    scheme: String,
+    credentials: String,
+}
+
+impl Authorization {
+    pub fn new(scheme: impl Into<String>, credentials: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            credentials: credentials.into(),
+        }
+    }
+
+    pub fn basic(username: &str, password: &str) -> Self {
+        let credentials = base64::encode(format!("{}:{}", username, password));
+        Self::new("Basic", credentials)
+    }
+
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self::new("Bearer", token)
+    }
+
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    pub fn credentials(&self) -> &str {
+        &self.credentials
+    }
+
+    /// Username and password if the scheme is `Basic`.
+    pub fn as_basic(&self) -> Option<(String, String)> {
+        if !self.scheme.eq_ignore_ascii_case("Basic") {
+            return None;
+        }
+        let decoded = base64::decode(&self.credentials).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        Some((username.to_string(), password.to_string()))
+    }
+
+    /// Token if the scheme is `Bearer`.
+    pub fn as_bearer(&self) -> Option<&str> {
+        if self.scheme.eq_ignore_ascii_case("Bearer") {
+            Some(&self.credentials)
+        } else {
+            None
+        }
+    }
+}
+
+impl TypedHeader for Authorization {
+    fn name() -> HeaderName {
+        HeaderName::Authorization
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let value = to_str(value)?.trim();
+        let (scheme, credentials) = value.split_once(' ').unwrap_or((value, ""));
+        if !is_token(scheme) {
+            return Err(StatusCode::BadRequest);
+        }
+        Ok(Self::new(scheme, credentials.trim()))
+    }
+
+    fn encode(&self) -> HeaderValue {
+        if self.credentials.is_empty() {
+            self.scheme.clone().into_bytes()
+        } else {
+            format!("{} {}", self.scheme, self.credentials).into_bytes()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic() {
+        let authorization = Authorization::decode(b"basic dXNlcjpwYXNzOndvcmQ=").unwrap();
+        assert_eq!(
+            Some(("user".to_string(), "pass:word".to_string())),
+            authorization.as_basic()
+        );
+        assert_eq!(None, authorization.as_bearer());
+        assert_eq!(
+            b"Basic dXNlcjpwYXNz".to_vec(),
+            Authorization::basic("user", "pass").encode()
+        );
+    }
+
+    #[test]
+    fn bearer() {
+        let authorization = Authorization::decode(b"Bearer mF_9.B5f-4.1JqM").unwrap();
+        assert_eq!(Some("mF_9.B5f-4.1JqM"), authorization.as_bearer());
+        assert_eq!(None, authorization.as_basic());
+        assert!(Authorization::decode(b"").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use super::{is_token, quote, split_quoted, to_str, unquote, TypedHeader};
+use crate::{
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// `Cache-Control` header, e.g. `public, max-age=3600`.
+/// Names of directives are case-insensitive, so they are kept in lowercase.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    directives: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_directive(mut self, name: &str, value: Option<&str>) -> Self {
+        let name = name.to_ascii_lowercase();
+        self.directives.retain(|(n, _)| *n != name);
+        self.directives.push((name, value.map(str::to_string)));
+        self
+    }
+
+    pub fn set_max_age(self, secs: u64) -> Self {
+        self.set_directive("max-age", Some(&secs.to_string()))
+    }
+
+    pub fn set_no_cache(self) -> Self {
+        self.set_directive("no-cache", None)
+    }
+
+    pub fn set_no_store(self) -> Self {
+        self.set_directive("no-store", None)
+    }
+
+    pub fn set_public(self) -> Self {
+        self.set_directive("public", None)
+    }
+
+    pub fn set_private(self) -> Self {
+        self.set_directive("private", None)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.directives
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Argument of the directive, e.g. `3600` of `max-age=3600`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.directives
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    pub fn max_age(&self) -> Option<u64> {
+        self.get("max-age")?.parse().ok()
+    }
+
+    pub fn no_cache(&self) -> bool {
+        self.contains("no-cache")
+    }
+
+    pub fn no_store(&self) -> bool {
+        self.contains("no-store")
+    }
+}
+
+impl TypedHeader for CacheControl {
+    fn name() -> HeaderName {
+        HeaderName::CacheControl
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let directives = split_quoted(to_str(value)?, ',')
+            .into_iter()
+            .map(|directive| {
+                let (name, value) = match directive.split_once('=') {
+                    Some((name, value)) => (name.trim(), Some(unquote(value.trim())?)),
+                    None => (directive, None),
+                };
+                if !is_token(name) {
+                    return Err(StatusCode::BadRequest);
+                }
+                Ok((name.to_ascii_lowercase(), value))
+            })
+            .collect::<crate::Result<_, StatusCode>>()?;
+        Ok(Self { directives })
+    }
+
+    fn encode(&self) -> HeaderValue {
+        self.directives
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{}={}", name, quote(value)),
+                None => name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+            .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_cache_control() {
+        let cache_control =
+            CacheControl::decode(b"Public, max-age=3600, private=\"Set-Cookie\"").unwrap();
+        assert!(cache_control.contains("public"));
+        assert_eq!(Some(3600), cache_control.max_age());
+        assert_eq!(Some("Set-Cookie"), cache_control.get("private"));
+        assert!(!cache_control.no_store());
+        assert!(CacheControl::decode(b"max age=1").is_err());
+    }
+
+    #[test]
+    fn encode_cache_control() {
+        let cache_control = CacheControl::new()
+            .set_public()
+            .set_max_age(60)
+            .set_max_age(3600);
+        assert_eq!(b"public, max-age=3600".to_vec(), cache_control.encode());
+    }
+}
@@ -0,0 +1,108 @@
+use super::{is_token, parse_params, quote, split_quoted, to_str, TypedHeader};
+use crate::{
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// `Content-Type` header, e.g. `text/html; charset=utf-8`.
+/// A media type and names of parameters are case-insensitive, so they are kept in lowercase.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentType {
+    mime_type: String,
+    params: Vec<(String, String)>,
+}
+
+impl ContentType {
+    pub fn new(mime_type: impl Into<String>) -> Self {
+        Self {
+            mime_type: mime_type.into().to_ascii_lowercase(),
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with_param(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.params.push((name.to_ascii_lowercase(), value.into()));
+        self
+    }
+
+    /// Media type without parameters, e.g. `text/html`.
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+}
+
+/// Check if `mime_type` is `type/subtype`.
+pub(crate) fn is_mime_type(mime_type: &str) -> bool {
+    match mime_type.split_once('/') {
+        Some((ty, subtype)) => is_token(ty) && is_token(subtype),
+        None => false,
+    }
+}
+
+impl TypedHeader for ContentType {
+    fn name() -> HeaderName {
+        HeaderName::ContentType
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let elements = split_quoted(to_str(value)?, ';');
+        let (mime_type, params) = elements.split_first().ok_or(StatusCode::BadRequest)?;
+        if !is_mime_type(mime_type) {
+            return Err(StatusCode::BadRequest);
+        }
+        Ok(Self {
+            mime_type: mime_type.to_ascii_lowercase(),
+            params: parse_params(params.iter().copied())?,
+        })
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let mut value = self.mime_type.clone();
+        for (name, param) in &self.params {
+            value.push_str(&format!("; {}={}", name, quote(param)));
+        }
+        value.into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_content_type() {
+        let content_type =
+            ContentType::decode(b"Multipart/Form-Data; Boundary=\"a b\"; charset=UTF-8").unwrap();
+        assert_eq!("multipart/form-data", content_type.mime_type());
+        assert_eq!(Some("a b"), content_type.param("boundary"));
+        assert_eq!(Some("UTF-8"), content_type.charset());
+        assert!(ContentType::decode(b"text").is_err());
+        assert!(ContentType::decode(b"text/html; charset").is_err());
+    }
+
+    #[test]
+    fn encode_content_type() {
+        let content_type = ContentType::new("text/html")
+            .with_param("charset", "utf-8")
+            .with_param("note", "a b");
+        assert_eq!(
+            b"text/html; charset=utf-8; note=\"a b\"".to_vec(),
+            content_type.encode()
+        );
+    }
+}
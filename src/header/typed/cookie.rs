@@ -0,0 +1,104 @@
+use super::{is_token, to_str, TypedHeader};
+use crate::{
+    cookie::{is_name_octet, is_value_octet, percent_decode, percent_encode},
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// `Cookie` header sent by a client, e.g. `session=38afes7a8; theme=dark`.
+/// Malformed pairs are skipped because clients send whatever servers set.
+/// Names and values are percent-decoded as `cookie::Cookie` encodes them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cookie {
+    pairs: Vec<(String, String)>,
+}
+
+impl Cookie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.pairs.push((name.into(), value.into()));
+        self
+    }
+
+    /// Value of the first cookie named `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+impl TypedHeader for Cookie {
+    // A comma is allowed in a value, so pairs in multiple lines are joined as one list.
+    const SEPARATOR: &'static [u8] = b"; ";
+
+    fn name() -> HeaderName {
+        HeaderName::Cookie
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let pairs = to_str(value)?
+            .split(';')
+            .filter_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                let value = value.strip_prefix('"').unwrap_or(value);
+                let value = value.strip_suffix('"').unwrap_or(value);
+                if is_token(name) {
+                    Some((
+                        percent_decode(name).into_owned(),
+                        percent_decode(value).into_owned(),
+                    ))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        Ok(Self { pairs })
+    }
+
+    fn encode(&self) -> HeaderValue {
+        self.iter()
+            .map(|(name, value)| {
+                format!(
+                    "{}={}",
+                    percent_encode(name, is_name_octet),
+                    percent_encode(value, is_value_octet)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+            .into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_cookie() {
+        let cookie = Cookie::decode(b"session=38afes7a8; theme=\"dark\"; broken; =x").unwrap();
+        assert_eq!(Some("38afes7a8"), cookie.get("session"));
+        assert_eq!(Some("dark"), cookie.get("theme"));
+        assert_eq!(2, cookie.iter().count());
+        let cookie = Cookie::decode(b"a%3Bb=x%3B%20y; c=%zz").unwrap();
+        assert_eq!(Some("x; y"), cookie.get("a;b"));
+        assert_eq!(Some("%zz"), cookie.get("c"));
+    }
+
+    #[test]
+    fn encode_cookie() {
+        let cookie = Cookie::new().with("a", "1").with("b", "2");
+        assert_eq!(b"a=1; b=2".to_vec(), cookie.encode());
+    }
+}
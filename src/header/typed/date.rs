@@ -0,0 +1,57 @@
+use super::TypedHeader;
+use crate::{
+    date::HttpDate,
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+use std::convert::TryFrom;
+
+macro_rules! define_date_headers {
+    ($(($(#[$doc:meta])* $name:ident),)+) => {
+        $(
+            $(#[$doc])*
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+            pub struct $name(pub HttpDate);
+
+            impl TypedHeader for $name {
+                fn name() -> HeaderName {
+                    HeaderName::$name
+                }
+
+                fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+                    HttpDate::try_from(value).map($name)
+                }
+
+                fn encode(&self) -> HeaderValue {
+                    self.0.to_string().into_bytes()
+                }
+            }
+        )+
+    };
+}
+
+define_date_headers!(
+    (/// `Date` header, which is the time the message is generated.
+    Date),
+    (/// `Expires` header, after which the response is regarded as stale.
+    Expires),
+    (/// `If-Modified-Since` header.
+    IfModifiedSince),
+    (/// `If-Unmodified-Since` header.
+    IfUnmodifiedSince),
+    (/// `Last-Modified` header.
+    LastModified),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn date_header() {
+        let date = Date(HttpDate::from(UNIX_EPOCH + Duration::from_secs(784111777)));
+        assert_eq!(b"Sun, 06 Nov 1994 08:49:37 GMT".to_vec(), date.encode());
+        assert_eq!(Ok(date), Date::decode(&date.encode()));
+    }
+}
@@ -0,0 +1,235 @@
+use super::{split_quoted, to_str, TypedHeader};
+use crate::{
+    date::HttpDate,
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// Entity tag, e.g. `"xyzzy"` or weak one `W/"xyzzy"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityTag {
+    weak: bool,
+    tag: String,
+}
+
+impl EntityTag {
+    /// Create a strong entity tag. `tag` must not contain `"`.
+    pub fn strong(tag: impl Into<String>) -> Self {
+        Self {
+            weak: false,
+            tag: tag.into(),
+        }
+    }
+
+    /// Create a weak entity tag. `tag` must not contain `"`.
+    pub fn weak(tag: impl Into<String>) -> Self {
+        Self {
+            weak: true,
+            tag: tag.into(),
+        }
+    }
+
+    pub fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Opaque tag without quotes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Strong comparison, where both tags must be strong.
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison, where weakness of tags is ignored.
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl FromStr for EntityTag {
+    type Err = StatusCode;
+
+    fn from_str(s: &str) -> crate::Result<Self, StatusCode> {
+        let (weak, quoted) = match s.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, s),
+        };
+        let tag = quoted
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+            .ok_or(StatusCode::BadRequest)?;
+        if tag.contains('"') {
+            return Err(StatusCode::BadRequest);
+        }
+        Ok(Self {
+            weak,
+            tag: tag.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weak {
+            write!(f, "W/")?;
+        }
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+/// `ETag` header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ETag(pub EntityTag);
+
+impl TypedHeader for ETag {
+    fn name() -> HeaderName {
+        HeaderName::ETag
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        to_str(value)?.trim().parse().map(ETag)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        self.0.to_string().into_bytes()
+    }
+}
+
+/// `If-None-Match` header, which is `*` or a list of entity tags.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IfNoneMatch {
+    Any,
+    Tags(Vec<EntityTag>),
+}
+
+impl IfNoneMatch {
+    /// Check if the current entity tag matches with weak comparison, which means the client's
+    /// cache is still valid.
+    pub fn matches(&self, etag: &EntityTag) -> bool {
+        match self {
+            IfNoneMatch::Any => true,
+            IfNoneMatch::Tags(tags) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        }
+    }
+}
+
+impl TypedHeader for IfNoneMatch {
+    fn name() -> HeaderName {
+        HeaderName::IfNoneMatch
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let value = to_str(value)?.trim();
+        if value == "*" {
+            return Ok(IfNoneMatch::Any);
+        }
+        split_quoted(value, ',')
+            .into_iter()
+            .map(str::parse)
+            .collect::<crate::Result<_, StatusCode>>()
+            .map(IfNoneMatch::Tags)
+    }
+
+    fn encode(&self) -> HeaderValue {
+        match self {
+            IfNoneMatch::Any => b"*".to_vec(),
+            IfNoneMatch::Tags(tags) => tags
+                .iter()
+                .map(EntityTag::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+                .into_bytes(),
+        }
+    }
+}
+
+/// `If-Range` header, which is an entity tag or a date.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IfRange {
+    ETag(EntityTag),
+    Date(HttpDate),
+}
+
+impl IfRange {
+    /// Check if the representation is not changed, which means ranges of it can be served.
+    /// Entity tags are compared with strong comparison, and dates must be exactly the same.
+    pub fn matches(&self, etag: Option<&EntityTag>, last_modified: Option<HttpDate>) -> bool {
+        match self {
+            IfRange::ETag(tag) => etag.is_some_and(|etag| tag.strong_eq(etag)),
+            IfRange::Date(date) => Some(*date) == last_modified,
+        }
+    }
+}
+
+impl TypedHeader for IfRange {
+    fn name() -> HeaderName {
+        HeaderName::IfRange
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let value = to_str(value)?.trim();
+        if value.starts_with('"') || value.starts_with("W/") {
+            value.parse().map(IfRange::ETag)
+        } else {
+            HttpDate::try_from(value.as_bytes()).map(IfRange::Date)
+        }
+    }
+
+    fn encode(&self) -> HeaderValue {
+        match self {
+            IfRange::ETag(tag) => tag.to_string().into_bytes(),
+            IfRange::Date(date) => date.to_string().into_bytes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_entity_tag() {
+        assert_eq!(Ok(EntityTag::strong("xyzzy")), "\"xyzzy\"".parse());
+        assert_eq!(Ok(EntityTag::weak("xyzzy")), "W/\"xyzzy\"".parse());
+        assert!("xyzzy".parse::<EntityTag>().is_err());
+        assert!("\"xy\"zzy\"".parse::<EntityTag>().is_err());
+        assert_eq!("W/\"xyzzy\"", EntityTag::weak("xyzzy").to_string());
+    }
+
+    #[test]
+    fn compare_entity_tags() {
+        let strong = EntityTag::strong("1");
+        let weak = EntityTag::weak("1");
+        assert!(strong.strong_eq(&strong));
+        assert!(!strong.strong_eq(&weak));
+        assert!(strong.weak_eq(&weak));
+        assert!(!strong.weak_eq(&EntityTag::strong("2")));
+    }
+
+    #[test]
+    fn if_none_match() {
+        let if_none_match = IfNoneMatch::decode(b"\"a\", W/\"b\"").unwrap();
+        assert!(if_none_match.matches(&EntityTag::strong("b")));
+        assert!(!if_none_match.matches(&EntityTag::strong("c")));
+        assert!(IfNoneMatch::decode(b"*")
+            .unwrap()
+            .matches(&EntityTag::strong("c")));
+    }
+
+    #[test]
+    fn if_range() {
+        let etag = EntityTag::strong("a");
+        assert!(IfRange::decode(b"\"a\"")
+            .unwrap()
+            .matches(Some(&etag), None));
+        assert!(!IfRange::decode(b"W/\"a\"")
+            .unwrap()
+            .matches(Some(&etag), None));
+        let date = IfRange::decode(b"Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert!(!date.matches(Some(&etag), None));
+    }
+}
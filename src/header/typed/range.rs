@@ -0,0 +1,143 @@
+use super::{to_str, TypedHeader};
+use crate::{
+    header::{HeaderName, HeaderValue},
+    status::StatusCode,
+};
+
+/// Range of bytes in `Range` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRangeSpec {
+    /// e.g. `500-999`. Both ends are inclusive.
+    FromTo(u64, u64),
+    /// e.g. `500-`, which means bytes from 500th to the end.
+    From(u64),
+    /// e.g. `-500`, which means last 500 bytes.
+    Last(u64),
+}
+
+impl ByteRangeSpec {
+    /// Resolve this range against length of a representation into inclusive range of bytes.
+    /// `None` is returned if the range is not satisfiable.
+    pub fn to_satisfiable_range(&self, len: u64) -> Option<(u64, u64)> {
+        let (start, end) = match *self {
+            ByteRangeSpec::FromTo(first, last) => (first, last.min(len.checked_sub(1)?)),
+            ByteRangeSpec::From(first) => (first, len.checked_sub(1)?),
+            ByteRangeSpec::Last(0) => return None,
+            ByteRangeSpec::Last(suffix_len) => {
+                (len.saturating_sub(suffix_len), len.checked_sub(1)?)
+            }
+        };
+        if start < len {
+            Some((start, end))
+        } else {
+            None
+        }
+    }
+}
+
+/// `Range` header in bytes, e.g. `bytes=0-499, -500`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range {
+    specs: Vec<ByteRangeSpec>,
+}
+
+impl Range {
+    pub fn bytes(specs: Vec<ByteRangeSpec>) -> Self {
+        Self { specs }
+    }
+
+    pub fn specs(&self) -> &[ByteRangeSpec] {
+        &self.specs
+    }
+
+    /// Resolve ranges against length of a representation. Unsatisfiable ranges are removed.
+    pub fn satisfiable_ranges(&self, len: u64) -> Vec<(u64, u64)> {
+        self.specs
+            .iter()
+            .filter_map(|spec| spec.to_satisfiable_range(len))
+            .collect()
+    }
+}
+
+impl TypedHeader for Range {
+    fn name() -> HeaderName {
+        HeaderName::Range
+    }
+
+    fn decode(value: &[u8]) -> crate::Result<Self, StatusCode> {
+        let specs = to_str(value)?
+            .trim()
+            .strip_prefix("bytes=")
+            .ok_or(StatusCode::BadRequest)?;
+        // `parse()` accepts a sign, which is not allowed in a range.
+        let parse = |n: &str| {
+            if !n.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(StatusCode::BadRequest);
+            }
+            n.parse::<u64>().or(Err(StatusCode::BadRequest))
+        };
+        let specs = specs
+            .split(',')
+            .map(|spec| {
+                let (first, last) = spec.trim().split_once('-').ok_or(StatusCode::BadRequest)?;
+                match (first, last) {
+                    ("", last) => Ok(ByteRangeSpec::Last(parse(last)?)),
+                    (first, "") => Ok(ByteRangeSpec::From(parse(first)?)),
+                    (first, last) => {
+                        let (first, last) = (parse(first)?, parse(last)?);
+                        if last < first {
+                            return Err(StatusCode::BadRequest);
+                        }
+                        Ok(ByteRangeSpec::FromTo(first, last))
+                    }
+                }
+            })
+            .collect::<crate::Result<_, StatusCode>>()?;
+        Ok(Self { specs })
+    }
+
+    fn encode(&self) -> HeaderValue {
+        let specs = self
+            .specs
+            .iter()
+            .map(|spec| match spec {
+                ByteRangeSpec::FromTo(first, last) => format!("{}-{}", first, last),
+                ByteRangeSpec::From(first) => format!("{}-", first),
+                ByteRangeSpec::Last(suffix_len) => format!("-{}", suffix_len),
+            })
+            .collect::<Vec<_>>();
+        format!("bytes={}", specs.join(", ")).into_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_range() {
+        let range = Range::decode(b"bytes=0-499, 500-,-100").unwrap();
+        assert_eq!(
+            &[
+                ByteRangeSpec::FromTo(0, 499),
+                ByteRangeSpec::From(500),
+                ByteRangeSpec::Last(100)
+            ],
+            range.specs()
+        );
+        assert_eq!(b"bytes=0-499, 500-, -100".to_vec(), range.encode());
+        assert!(Range::decode(b"bytes=9-0").is_err());
+        assert!(Range::decode(b"bytes=-").is_err());
+        assert!(Range::decode(b"items=0-9").is_err());
+    }
+
+    #[test]
+    fn satisfiable_ranges() {
+        let range = Range::decode(b"bytes=0-0,900-2000,1000-,-0,-2000").unwrap();
+        assert_eq!(
+            vec![(0, 0), (900, 999), (0, 999)],
+            range.satisfiable_ranges(1000)
+        );
+        assert_eq!(Vec::<(u64, u64)>::new(), range.satisfiable_ranges(0));
+    }
+}
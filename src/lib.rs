@@ -13,10 +13,10 @@
 //! # Examples
 //!
 //! ```no_run
-//! use qz::{method::Method, request::Request, response::Response, server::Server};
+//! use qz::{method::Method, request::Request, response::IntoResponse, server::Server};
 //! use std::io;
 //!
-//! async fn hello(_request: Request, _: ()) -> impl Into<Response> {
+//! async fn hello(_request: Request, _: ()) -> impl IntoResponse {
 //!     "hello"
 //! }
 //!
@@ -36,6 +36,12 @@
 //!
 
 pub mod body;
+pub mod cookie;
+pub mod date;
+mod error;
+pub mod error_handler;
+mod extensions;
+pub mod extract;
 pub mod handler;
 pub mod header;
 pub mod method;
@@ -49,52 +55,25 @@ mod router;
 pub mod server;
 pub mod static_files;
 pub mod status;
+mod uri;
 
-use crate::status::StatusCode;
-use std::{
-    convert::{From, TryFrom},
-    fmt, str,
+pub use crate::{
+    error::Error,
+    extensions::Extensions,
+    uri::{Uri, UriPath},
 };
 
-/// All errornous function returns this type. because all error in this crate converges HTTP error which is
-/// represented by status code.
-pub type Result<T, E = StatusCode> = std::result::Result<T, E>;
-
-#[derive(Debug, PartialEq, Eq)]
-pub struct Uri(Vec<u8>);
-
-impl Uri {
-    pub fn new(path: &[u8]) -> Self {
-        Self(path.to_vec())
-    }
-}
-
-impl From<&str> for Uri {
-    fn from(s: &str) -> Self {
-        Uri(s.as_bytes().to_vec())
-    }
-}
-
-impl AsRef<[u8]> for Uri {
-    fn as_ref(&self) -> &[u8] {
-        &self.0
-    }
-}
-
-impl Default for Uri {
-    fn default() -> Self {
-        Uri(b"/".to_vec())
-    }
-}
+use crate::status::StatusCode;
+use std::{convert::TryFrom, fmt};
 
-impl fmt::Display for Uri {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", str::from_utf8(&self.0).unwrap())
-    }
-}
+/// All errornous function returns this type, because all error in this crate converges HTTP error.
+/// Parsers of HTTP messages use `Result<T, StatusCode>`, and errors sent to clients with a
+/// description use `Error`.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Version {
+    #[default]
     OneDotOne,
 }
 
@@ -106,12 +85,6 @@ impl Version {
     }
 }
 
-impl Default for Version {
-    fn default() -> Self {
-        Self::OneDotOne
-    }
-}
-
 impl fmt::Display for Version {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -122,7 +95,7 @@ impl fmt::Display for Version {
 
 impl TryFrom<&[u8]> for Version {
     type Error = StatusCode;
-    fn try_from(value: &[u8]) -> Result<Self> {
+    fn try_from(value: &[u8]) -> Result<Self, StatusCode> {
         match value {
             b"1.1" => Ok(Version::OneDotOne),
             _ => Err(StatusCode::HttpVersionNotSupported),
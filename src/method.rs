@@ -1,37 +1,100 @@
-use crate::status::StatusCode;
+use crate::{parser::is_tchar, status::StatusCode};
 use std::{convert::TryFrom, fmt};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// HTTP request method. Methods which are not defined in RFC 7231 and RFC 5789 are represented as
+/// `Extension`, e.g. `PURGE` or WebDAV's `PROPFIND`. Methods are case-sensitive, so `get` is an
+/// extension method.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub enum Method {
+    #[default]
     Get,
+    Head,
     Post,
+    Put,
+    Delete,
+    Connect,
     Options,
+    Trace,
+    Patch,
+    Extension(String),
 }
 
-impl Default for Method {
-    fn default() -> Self {
-        Self::Get
+impl Method {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Get => "GET",
+            Method::Head => "HEAD",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Delete => "DELETE",
+            Method::Connect => "CONNECT",
+            Method::Options => "OPTIONS",
+            Method::Trace => "TRACE",
+            Method::Patch => "PATCH",
+            Method::Extension(method) => method,
+        }
     }
 }
 
 impl fmt::Display for Method {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Method::Get => write!(f, "GET"),
-            Method::Post => write!(f, "POST"),
-            Method::Options => write!(f, "OPTIONS"),
-        }
+        write!(f, "{}", self.as_str())
     }
 }
 
 impl TryFrom<&[u8]> for Method {
     type Error = StatusCode;
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        match std::str::from_utf8(value) {
-            Ok("GET") => Ok(Method::Get),
-            Ok("POST") => Ok(Method::Post),
-            Ok("OPTONS") => Ok(Method::Options),
-            _ => Err(StatusCode::MethodNotAllowed),
-        }
+        let method = match value {
+            b"GET" => Method::Get,
+            b"HEAD" => Method::Head,
+            b"POST" => Method::Post,
+            b"PUT" => Method::Put,
+            b"DELETE" => Method::Delete,
+            b"CONNECT" => Method::Connect,
+            b"OPTIONS" => Method::Options,
+            b"TRACE" => Method::Trace,
+            b"PATCH" => Method::Patch,
+            _ if !value.is_empty() && value.iter().all(|&b| is_tchar(b)) => {
+                // All of the bytes are ASCII, so it never fails.
+                Method::Extension(String::from_utf8(value.to_vec()).unwrap())
+            }
+            _ => return Err(StatusCode::BadRequest),
+        };
+        Ok(method)
+    }
+}
+
+impl TryFrom<&str> for Method {
+    type Error = StatusCode;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Method::try_from(value.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_method() {
+        assert_eq!(Ok(Method::Options), Method::try_from("OPTIONS"));
+        assert_eq!(Ok(Method::Patch), Method::try_from("PATCH"));
+        assert_eq!(
+            Ok(Method::Extension("PURGE".to_string())),
+            Method::try_from("PURGE")
+        );
+        assert_eq!(
+            Ok(Method::Extension("get".to_string())),
+            Method::try_from("get")
+        );
+        assert_eq!(Err(StatusCode::BadRequest), Method::try_from(""));
+        assert_eq!(Err(StatusCode::BadRequest), Method::try_from("GE(T"));
+    }
+
+    #[test]
+    fn format_method() {
+        assert_eq!("DELETE", Method::Delete.to_string());
+        assert_eq!("PURGE", Method::Extension("PURGE".to_string()).to_string());
     }
 }
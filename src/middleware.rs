@@ -1,13 +1,19 @@
 use async_trait::async_trait;
 
-use crate::{handler::Handler, request::Request, response::Response};
+use crate::{
+    handler::Handler,
+    request::Request,
+    response::{IntoResponse, Response},
+};
 use std::{future::Future, sync::Arc};
 
 mod basic_auth;
 mod cors;
+mod session;
 
-pub use basic_auth::BasicAuth;
+pub use basic_auth::{AuthenticatedUser, BasicAuth};
 pub use cors::Cors;
+pub use session::{FileStore, MemoryStore, Session, SessionHandle, SessionRecord, SessionStore};
 
 /// Middleware preprocesses request before generating response in `Handler` and postprocesses
 /// response.
@@ -28,7 +34,8 @@ where
 impl<F, Fut, State> Middleware<State> for F
 where
     F: Send + Sync + 'static + Fn(Request, State, MiddlewareChain<'_, State>) -> Fut,
-    Fut: Future<Output = Response> + Send + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: IntoResponse,
     State: Clone + Send + Sync + 'static,
 {
     async fn call(
@@ -37,7 +44,7 @@ where
         state: State,
         next: MiddlewareChain<'_, State>,
     ) -> Response {
-        self(request, state, next).await
+        self(request, state, next).await.into_response()
     }
 }
 
@@ -64,10 +71,7 @@ where
             self.middlewares = remaining;
             first.call(request, state, self).await
         } else {
-            match self.handler.call(request, state).await {
-                Ok(response) => response,
-                Err(code) => code.into(),
-            }
+            self.handler.call(request, state).await.into_response()
         }
     }
 }
@@ -82,6 +86,7 @@ mod tests {
     };
 
     use super::*;
+    use std::convert::TryFrom;
 
     #[tokio::test]
     async fn middleware_chain_handler_only() {
@@ -108,7 +113,7 @@ mod tests {
                 // These headers here has no meaning.
                 request.set_header(HeaderName::Accept, "*/*");
                 let mut response = next.run(request, state).await;
-                response.set_header(HeaderName::Unknown, "hello");
+                response.set_header(HeaderName::try_from("X-Dummy").unwrap(), "hello");
                 response
             }
         }
@@ -151,7 +156,11 @@ mod tests {
         }
 
         let server = ServerBuilder::new()
-            .route("/", Method::Get, Box::new(|_, _| async { "hello" }))
+            .route(
+                "/",
+                Method::Get,
+                Box::new(|_: Request, _: ()| async { "hello" }),
+            )
             .with(DummyA)
             .with(DummyB)
             .with(Test)
@@ -160,7 +169,7 @@ mod tests {
         let response = server.respond(request).await;
         assert_eq!(
             Some(&HeaderValue::from("hello")),
-            response.get_header(&HeaderName::Unknown)
+            response.get_header(&HeaderName::try_from("X-Dummy").unwrap())
         );
         assert_eq!(
             Some(&HeaderValue::from("example.com")),
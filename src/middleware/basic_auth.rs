@@ -1,53 +1,64 @@
 use crate::{
-    header::HeaderName, middleware::Middleware, request::Request, response::Response,
-    status::StatusCode, Uri,
+    header::{typed::Authorization, HeaderName},
+    middleware::Middleware,
+    request::Request,
+    response::Response,
+    status::StatusCode,
+    Uri,
 };
 use async_trait::async_trait;
 
 use super::MiddlewareChain;
 
+/// User authenticated by `BasicAuth`, which is available to the handler with
+/// `request.extension::<AuthenticatedUser>()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuthenticatedUser {
+    username: String,
+}
+
+impl AuthenticatedUser {
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+}
+
 #[derive(Debug)]
 pub struct BasicAuth {
-    credential_hash: Vec<u8>,
+    username: String,
+    password: String,
     auth_root: Uri,
 }
 
 impl BasicAuth {
     /// Create new Basic authentication middleware. `auth_root` is the root of subtree to protect
     pub fn new(username: &str, password: &str, auth_root: impl Into<Uri>) -> Self {
-        let credential = format!("{}:{}", username, password);
-        let credential_hash = base64::encode(credential.as_bytes()).into_bytes();
         Self {
-            credential_hash,
+            username: username.to_string(),
+            password: password.to_string(),
             auth_root: auth_root.into(),
         }
     }
 
     fn is_protected_uri(&self, uri: &Uri) -> bool {
-        uri.0.starts_with(&self.auth_root.0)
+        // Compare decoded paths so that `/%61dmin` or `/public/../admin` cannot bypass
+        // authentication of `/admin`. A path which cannot be decoded is protected to be safe.
+        match (uri.decoded_path(), self.auth_root.decoded_path()) {
+            (Ok(path), Ok(auth_root)) => path.as_str().starts_with(auth_root.as_str()),
+            _ => true,
+        }
     }
 
-    fn check_credential(&self, request: &Request) -> crate::Result<()> {
-        let credential = request
-            .get_header(HeaderName::Authorization)
-            .ok_or(StatusCode::Unauthorized)?;
-        // credential = [b"Basic:", b"xxxxxxx"]
-        let mut credential = credential.split(|&c| c == b' ');
-        credential
-            .next()
-            .and_then(|c| if c == b"Basic" { Some(()) } else { None })
+    fn check_credential(&self, request: &Request) -> crate::Result<AuthenticatedUser, StatusCode> {
+        let (username, password) = request
+            .typed_header::<Authorization>()
+            .and_then(|authorization| authorization.as_basic())
             .ok_or(StatusCode::Unauthorized)?;
-        credential
-            .next()
-            .and_then(|hash| {
-                if hash == self.credential_hash {
-                    Some(())
-                } else {
-                    None
-                }
-            })
-            .ok_or(StatusCode::Unauthorized)?;
-        Ok(())
+        if username == self.username && password == self.password {
+            Ok(AuthenticatedUser { username })
+        } else {
+            Err(StatusCode::Unauthorized)
+        }
     }
 }
 
@@ -58,7 +69,7 @@ where
 {
     async fn call(
         &self,
-        request: Request,
+        mut request: Request,
         state: State,
         next: MiddlewareChain<'_, State>,
     ) -> Response
@@ -67,11 +78,16 @@ where
     {
         let uri = request.uri();
         if self.is_protected_uri(uri) {
-            if let Err(code) = self.check_credential(&request) {
-                assert_eq!(StatusCode::Unauthorized, code);
-                let mut response = Response::from(code);
-                response.set_header(HeaderName::WwwAuthenticate, "Basic");
-                return response;
+            match self.check_credential(&request) {
+                Ok(user) => {
+                    request.insert_extension(user);
+                }
+                Err(code) => {
+                    assert_eq!(StatusCode::Unauthorized, code);
+                    let mut response = Response::from(code);
+                    response.set_header(HeaderName::WwwAuthenticate, "Basic");
+                    return response;
+                }
             }
         }
         next.run(request, state).await
@@ -85,7 +101,7 @@ mod tests {
     use super::*;
 
     fn server() -> ServerBuilder<()> {
-        ServerBuilder::new().route("/", Method::Get, |_, _| async { "Hello" })
+        ServerBuilder::new().route("/", Method::Get, || async { "Hello" })
     }
 
     #[tokio::test]
@@ -98,6 +114,22 @@ mod tests {
         assert_eq!(StatusCode::Ok, response.status_code());
     }
 
+    #[tokio::test]
+    async fn authenticated_user() {
+        let server = ServerBuilder::new()
+            .route("/", Method::Get, |request: Request, _: ()| async move {
+                let user = request.extension::<AuthenticatedUser>().unwrap();
+                format!("Hello, {}", user.username())
+            })
+            .with(BasicAuth::new("user", "pass", "/"))
+            .build();
+        let request = Request::builder()
+            .set_header(HeaderName::Authorization, "Basic dXNlcjpwYXNz")
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("Hello, user"), response.body());
+    }
+
     #[tokio::test]
     async fn not_protected_by_basic_auth() {
         let server = server()
@@ -108,6 +140,19 @@ mod tests {
         assert_eq!(StatusCode::Ok, response.status_code());
     }
 
+    #[tokio::test]
+    async fn protected_by_basic_auth_with_encoded_path() {
+        let server = server()
+            .route("/admin", Method::Get, || async { "Admin" })
+            .with(BasicAuth::new("user", "pass", "/admin"))
+            .build();
+        for uri in ["/%61dmin", "/public/../admin", "//admin"] {
+            let request = Request::builder().set_uri(uri).build();
+            let response = server.respond(request).await;
+            assert_eq!(StatusCode::Unauthorized, response.status_code());
+        }
+    }
+
     #[tokio::test]
     async fn fail_basic_auth() {
         let server = server().with(BasicAuth::new("user", "pass", "/")).build();
@@ -78,6 +78,12 @@ impl Cors {
     }
 }
 
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[async_trait]
 impl<State> Middleware<State> for Cors
 where
@@ -97,7 +103,13 @@ where
             return Response::new(StatusCode::Unauthorized);
         }
 
-        if request.method() == Method::Options {
+        // `OPTIONS` without `Access-Control-Request-Method` is not a preflight request, so it is
+        // answered by the router with `Allow` header.
+        if *request.method() == Method::Options
+            && request
+                .get_header(HeaderName::AccessControlRequestMethod)
+                .is_some()
+        {
             return self.handle_preflight();
         }
 
@@ -121,7 +133,7 @@ pub enum Origin {
 
 impl From<Vec<u8>> for Origin {
     fn from(v: Vec<u8>) -> Self {
-        if v == &[b'*'] {
+        if v == b"*" {
             return Self::Any;
         }
         Self::Single(v)
@@ -173,7 +185,7 @@ mod tests {
     const ALLOW_ORIGIN: &[u8] = b"localhost:3000";
 
     fn server() -> ServerBuilder<()> {
-        ServerBuilder::new().route("/", Method::Get, |_, _| async { "Hello" })
+        ServerBuilder::new().route("/", Method::Get, || async { "Hello" })
     }
 
     // Simple request is a request which does not need a preflight request.
@@ -233,6 +245,7 @@ mod tests {
         let request = Request::builder()
             .set_method(Method::Options)
             .set_header(HeaderName::Origin, ALLOW_ORIGIN)
+            .set_header(HeaderName::AccessControlRequestMethod, "POST")
             .build();
         let response = server.respond(request).await;
         assert_eq!(StatusCode::Ok, response.status_code());
@@ -266,4 +279,25 @@ mod tests {
                 .clone()
         );
     }
+
+    #[tokio::test]
+    async fn options_request_not_preflight() {
+        let server = server()
+            .with(Cors::new().allow_origin(ALLOW_ORIGIN))
+            .build();
+        let request = Request::builder()
+            .set_method(Method::Options)
+            .set_header(HeaderName::Origin, ALLOW_ORIGIN)
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(StatusCode::NoContent, response.status_code());
+        assert_eq!(
+            Some(&b"GET, HEAD, OPTIONS".to_vec()),
+            response.get_header(&HeaderName::Allow)
+        );
+        assert_eq!(
+            Some(&ALLOW_ORIGIN.to_vec()),
+            response.get_header(&HeaderName::AccessControlAllowOrigin)
+        );
+    }
 }
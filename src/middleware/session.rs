@@ -0,0 +1,633 @@
+mod file_store;
+mod memory_store;
+
+pub use file_store::FileStore;
+pub use memory_store::MemoryStore;
+
+use crate::{
+    cookie::{Cookie, SameSite},
+    middleware::{Middleware, MiddlewareChain},
+    request::Request,
+    response::{IntoResponse, Response},
+    status::StatusCode,
+    Error,
+};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt, io,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+const DEFAULT_COOKIE_NAME: &str = "qz.sid";
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+const DEFAULT_ABSOLUTE_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+const DEFAULT_CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+const ID_LEN: usize = 32;
+
+/// Session data saved in `SessionStore`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub values: HashMap<String, serde_json::Value>,
+    pub created_at: SystemTime,
+    pub last_accessed: SystemTime,
+    /// When the session expires unless it is accessed again.
+    pub expires_at: SystemTime,
+}
+
+impl SessionRecord {
+    fn new() -> Self {
+        let now = SystemTime::now();
+        Self {
+            id: String::new(),
+            values: HashMap::new(),
+            created_at: now,
+            last_accessed: now,
+            expires_at: now,
+        }
+    }
+}
+
+/// Backend to save sessions. `id` passed to the methods is always a value generated by `Session`
+/// middleware, which consists of only ASCII alphanumerics, `-` and `_`.
+#[async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// Load the session. `None` is returned if it does not exist.
+    async fn load(&self, id: &str) -> io::Result<Option<SessionRecord>>;
+
+    /// Save the session overwriting one with the same ID.
+    async fn store(&self, record: &SessionRecord) -> io::Result<()>;
+
+    /// Delete the session. Deleting a session which does not exist is not an error.
+    async fn destroy(&self, id: &str) -> io::Result<()>;
+
+    /// Delete sessions whose `expires_at` is not later than `now`.
+    /// `Session` middleware calls this periodically so that abandoned sessions do not pile up.
+    /// A store which expires sessions by itself does not have to implement this.
+    async fn cleanup(&self, _now: SystemTime) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SessionState {
+    record: SessionRecord,
+    is_new: bool,
+    changed: bool,
+    regenerate: bool,
+    destroyed: bool,
+}
+
+/// Session of the client which sent the request, available with `Request::session()` when
+/// `Session` middleware is used. Changes are saved after the handler returns a response.
+#[derive(Clone)]
+pub struct SessionHandle {
+    inner: Arc<Mutex<SessionState>>,
+}
+
+impl SessionHandle {
+    fn new(record: Option<SessionRecord>) -> Self {
+        let is_new = record.is_none();
+        let state = SessionState {
+            record: record.unwrap_or_else(SessionRecord::new),
+            is_new,
+            changed: false,
+            regenerate: false,
+            destroyed: false,
+        };
+        Self {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SessionState> {
+        self.inner.lock().unwrap()
+    }
+
+    /// ID of the session. `None` is returned if the session has not been saved yet.
+    pub fn id(&self) -> Option<String> {
+        let state = self.state();
+        if state.is_new {
+            None
+        } else {
+            Some(state.record.id.clone())
+        }
+    }
+
+    /// Value of `key` deserialized as `T`. `None` is returned if it is missing or another type.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.state().record.values.get(key)?.clone();
+        serde_json::from_value(value).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: impl Into<String>, value: T) -> crate::Result<()> {
+        let value = serde_json::to_value(value).map_err(|err| {
+            Error::new(
+                StatusCode::InternalServerError,
+                "failed to serialize session value",
+            )
+            .set_source(err)
+        })?;
+        let mut state = self.state();
+        state.record.values.insert(key.into(), value);
+        state.changed = true;
+        Ok(())
+    }
+
+    pub fn remove(&self, key: &str) {
+        let mut state = self.state();
+        if state.record.values.remove(key).is_some() {
+            state.changed = true;
+        }
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.record.values.clear();
+        state.changed = true;
+    }
+
+    /// Issue a new ID keeping the values. Call this when the privilege of the client changes,
+    /// e.g. on login, to prevent session fixation.
+    pub fn regenerate(&self) {
+        let mut state = self.state();
+        state.regenerate = true;
+        state.changed = true;
+    }
+
+    /// Delete the session from the store and the client, e.g. on logout.
+    pub fn destroy(&self) {
+        self.state().destroyed = true;
+    }
+}
+
+impl fmt::Debug for SessionHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Values can be secret.
+        write!(f, "SessionHandle")
+    }
+}
+
+impl PartialEq for SessionHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for SessionHandle {}
+
+/// Middleware to load a session identified by a cookie before running following middlewares and
+/// the handler, and to save it after them.
+///
+/// A session expires when it is not accessed for `idle_timeout` or `absolute_timeout` passes
+/// since it is created. A session which is not changed is written back to the store only when a
+/// tenth of `idle_timeout` has passed since the last write, so it may expire that much earlier.
+/// Expired sessions are deleted from the store every `cleanup_interval`.
+///
+/// # Examples
+/// ```no_run
+/// use qz::{
+///     method::Method,
+///     middleware::{MemoryStore, Session},
+///     request::Request,
+///     server::Server,
+/// };
+///
+/// async fn count(request: Request, _: ()) -> String {
+///     let session = request.session().unwrap();
+///     let count = session.get::<u32>("count").unwrap_or(0) + 1;
+///     session.insert("count", count).unwrap();
+///     count.to_string()
+/// }
+///
+/// let server = Server::builder()
+///     .route("/", Method::Get, count)
+///     .with(Session::new(MemoryStore::new()))
+///     .build();
+/// ```
+pub struct Session<S> {
+    store: S,
+    cookie_name: String,
+    idle_timeout: Duration,
+    absolute_timeout: Duration,
+    secure: bool,
+    cleanup_interval: Duration,
+    last_cleanup: Mutex<SystemTime>,
+}
+
+impl<S: SessionStore> Session<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            cookie_name: DEFAULT_COOKIE_NAME.to_string(),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            absolute_timeout: DEFAULT_ABSOLUTE_TIMEOUT,
+            secure: false,
+            cleanup_interval: DEFAULT_CLEANUP_INTERVAL,
+            last_cleanup: Mutex::new(SystemTime::now()),
+        }
+    }
+
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        self.cookie_name = name.into();
+        self
+    }
+
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    pub fn absolute_timeout(mut self, timeout: Duration) -> Self {
+        self.absolute_timeout = timeout;
+        self
+    }
+
+    /// Send the session cookie only over HTTPS.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Set how often expired sessions are deleted from the store.
+    pub fn cleanup_interval(mut self, interval: Duration) -> Self {
+        self.cleanup_interval = interval;
+        self
+    }
+
+    fn is_expired(&self, record: &SessionRecord, now: SystemTime) -> bool {
+        let expired = |since: SystemTime, timeout: Duration| {
+            now.duration_since(since)
+                .is_ok_and(|elapsed| elapsed > timeout)
+        };
+        expired(record.last_accessed, self.idle_timeout)
+            || expired(record.created_at, self.absolute_timeout)
+    }
+
+    fn expires_at(&self, record: &SessionRecord) -> SystemTime {
+        std::cmp::min(
+            record.last_accessed + self.idle_timeout,
+            record.created_at + self.absolute_timeout,
+        )
+    }
+
+    async fn load(&self, request: &Request) -> io::Result<Option<SessionRecord>> {
+        let jar = request.cookies();
+        let id = match jar.get(&self.cookie_name) {
+            Some(id) if is_valid_id(id) => id,
+            _ => return Ok(None),
+        };
+        match self.store.load(id).await? {
+            Some(record) if self.is_expired(&record, SystemTime::now()) => {
+                self.store.destroy(id).await?;
+                Ok(None)
+            }
+            record => Ok(record),
+        }
+    }
+
+    async fn save(&self, session: &SessionHandle, response: &mut Response) -> io::Result<()> {
+        // Do not hold the lock across `await`.
+        let state = session.state().clone();
+        if state.destroyed {
+            if !state.is_new {
+                self.store.destroy(&state.record.id).await?;
+                response.set_cookie(Cookie::removal(&self.cookie_name).set_path("/"));
+            }
+            return Ok(());
+        }
+        // Do not save sessions of clients which have never used it.
+        if state.is_new && !state.changed {
+            return Ok(());
+        }
+        let now = SystemTime::now();
+        let needs_touch = now
+            .duration_since(state.record.last_accessed)
+            .is_ok_and(|elapsed| elapsed >= self.idle_timeout / 10);
+        if !state.changed && !needs_touch {
+            return Ok(());
+        }
+
+        let mut record = state.record;
+        if state.is_new || state.regenerate {
+            if !state.is_new {
+                self.store.destroy(&record.id).await?;
+            }
+            record.id = generate_id();
+            response.set_cookie(self.cookie(&record.id));
+        }
+        record.last_accessed = now;
+        record.expires_at = self.expires_at(&record);
+        self.store.store(&record).await
+    }
+
+    /// Delete expired sessions if `cleanup_interval` has passed since the last cleanup.
+    async fn cleanup(&self) -> io::Result<()> {
+        let now = SystemTime::now();
+        {
+            let mut last_cleanup = self.last_cleanup.lock().unwrap();
+            let is_due = now
+                .duration_since(*last_cleanup)
+                .is_ok_and(|elapsed| elapsed >= self.cleanup_interval);
+            if !is_due {
+                return Ok(());
+            }
+            *last_cleanup = now;
+        }
+        self.store.cleanup(now).await
+    }
+
+    fn cookie(&self, id: &str) -> Cookie {
+        Cookie::new(&self.cookie_name, id)
+            .set_path("/")
+            .set_http_only(true)
+            .set_secure(self.secure)
+            .set_same_site(SameSite::Lax)
+    }
+}
+
+#[async_trait]
+impl<S, State> Middleware<State> for Session<S>
+where
+    S: SessionStore,
+    State: Clone + Send + Sync + 'static,
+{
+    async fn call(
+        &self,
+        mut request: Request,
+        state: State,
+        next: MiddlewareChain<'_, State>,
+    ) -> Response {
+        let record = match self.load(&request).await {
+            Ok(record) => record,
+            Err(err) => return store_error("failed to load the session", err).into_response(),
+        };
+        let session = SessionHandle::new(record);
+        request.insert_extension(session.clone());
+        let mut response = next.run(request, state).await;
+        // A failed cleanup is retried after the next interval, so it does not fail the response.
+        let _ = self.cleanup().await;
+        match self.save(&session, &mut response).await {
+            Ok(()) => response,
+            Err(err) => store_error("failed to save the session", err).into_response(),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Session<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("cookie_name", &self.cookie_name)
+            .field("idle_timeout", &self.idle_timeout)
+            .field("absolute_timeout", &self.absolute_timeout)
+            .field("secure", &self.secure)
+            .field("cleanup_interval", &self.cleanup_interval)
+            .finish()
+    }
+}
+
+/// Failure of a store is an error of the server even if it is `NotFound` or `PermissionDenied`.
+fn store_error(message: &str, err: io::Error) -> Error {
+    Error::new(StatusCode::InternalServerError, message).set_source(err)
+}
+
+/// Generate a random ID encoded with URL-safe base64 so that it can be used in cookies and file
+/// names.
+fn generate_id() -> String {
+    let mut id = [0; ID_LEN];
+    OsRng.fill_bytes(&mut id);
+    base64::encode_config(id, base64::URL_SAFE_NO_PAD)
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == ID_LEN.div_ceil(3) * 4 - 1
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{header::HeaderName, method::Method, server::ServerBuilder};
+
+    async fn login(request: Request, _: ()) -> &'static str {
+        let session = request.session().unwrap();
+        session.insert("user", "alice").unwrap();
+        session.regenerate();
+        "logged in"
+    }
+
+    async fn whoami(request: Request, _: ()) -> String {
+        let session = request.session().unwrap();
+        session.get::<String>("user").unwrap_or_default()
+    }
+
+    async fn logout(request: Request, _: ()) -> &'static str {
+        request.session().unwrap().destroy();
+        "logged out"
+    }
+
+    fn server(session: Session<MemoryStore>) -> crate::server::Server<()> {
+        ServerBuilder::new()
+            .route("/login", Method::Get, login)
+            .route("/whoami", Method::Get, whoami)
+            .route("/logout", Method::Get, logout)
+            .with(session)
+            .build()
+    }
+
+    fn request(uri: &str, id: Option<&str>) -> Request {
+        let builder = Request::builder().set_uri(uri);
+        match id {
+            Some(id) => builder.set_header(HeaderName::Cookie, format!("qz.sid={}", id)),
+            None => builder,
+        }
+        .build()
+    }
+
+    fn session_id(response: &Response) -> Option<String> {
+        let cookie = response.get_header(&HeaderName::SetCookie)?;
+        let cookie = std::str::from_utf8(cookie).unwrap();
+        let (pair, _) = cookie.split_once(';')?;
+        Some(pair.strip_prefix("qz.sid=")?.to_string())
+    }
+
+    #[tokio::test]
+    async fn untouched_session_is_not_saved() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()));
+        let response = server.respond(request("/whoami", None)).await;
+        assert_eq!(None, response.get_header(&HeaderName::SetCookie));
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn login_and_logout() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()));
+        let response = server.respond(request("/login", None)).await;
+        let id = session_id(&response).unwrap();
+        assert!(is_valid_id(&id));
+        assert_eq!(1, store.len());
+
+        let response = server.respond(request("/whoami", Some(&id))).await;
+        assert_eq!(&crate::body::Body::from("alice"), response.body());
+        // The ID is not changed, so the cookie is not sent again.
+        assert_eq!(None, response.get_header(&HeaderName::SetCookie));
+
+        let response = server.respond(request("/logout", Some(&id))).await;
+        assert_eq!(Some(String::new()), session_id(&response));
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn regenerate_id() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()));
+        let response = server.respond(request("/login", None)).await;
+        let old_id = session_id(&response).unwrap();
+
+        let response = server.respond(request("/login", Some(&old_id))).await;
+        let new_id = session_id(&response).unwrap();
+        assert_ne!(old_id, new_id);
+        assert_eq!(None, store.load(&old_id).await.unwrap());
+        assert!(store.load(&new_id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn expire_session() {
+        let store = MemoryStore::new();
+        let server = server(
+            Session::new(store.clone())
+                .idle_timeout(Duration::from_secs(60))
+                .absolute_timeout(Duration::from_secs(3600)),
+        );
+        let response = server.respond(request("/login", None)).await;
+        let id = session_id(&response).unwrap();
+        let record = store.load(&id).await.unwrap().unwrap();
+
+        let idle = SessionRecord {
+            last_accessed: SystemTime::now() - Duration::from_secs(61),
+            ..record.clone()
+        };
+        store.store(&idle).await.unwrap();
+        let response = server.respond(request("/whoami", Some(&id))).await;
+        assert_eq!(&crate::body::Body::from(""), response.body());
+        assert!(store.is_empty());
+
+        let old = SessionRecord {
+            created_at: SystemTime::now() - Duration::from_secs(3601),
+            ..record
+        };
+        store.store(&old).await.unwrap();
+        let response = server.respond(request("/whoami", Some(&id))).await;
+        assert_eq!(&crate::body::Body::from(""), response.body());
+        assert!(store.is_empty());
+    }
+
+    #[tokio::test]
+    async fn write_back_only_when_needed() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()).idle_timeout(Duration::from_secs(100)));
+        let response = server.respond(request("/login", None)).await;
+        let id = session_id(&response).unwrap();
+        let record = store.load(&id).await.unwrap().unwrap();
+
+        server.respond(request("/whoami", Some(&id))).await;
+        assert_eq!(Some(record.clone()), store.load(&id).await.unwrap());
+
+        let last_accessed = SystemTime::now() - Duration::from_secs(11);
+        let stale = SessionRecord {
+            last_accessed,
+            ..record
+        };
+        store.store(&stale).await.unwrap();
+        server.respond(request("/whoami", Some(&id))).await;
+        let touched = store.load(&id).await.unwrap().unwrap();
+        assert!(touched.last_accessed > last_accessed);
+        assert_eq!(
+            touched.last_accessed + Duration::from_secs(100),
+            touched.expires_at
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_expired_sessions() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()).cleanup_interval(Duration::from_secs(0)));
+        let response = server.respond(request("/login", None)).await;
+        let id = session_id(&response).unwrap();
+        let mut abandoned = SessionRecord::new();
+        abandoned.id = generate_id();
+        abandoned.expires_at = SystemTime::now() - Duration::from_secs(1);
+        store.store(&abandoned).await.unwrap();
+        assert_eq!(2, store.len());
+
+        server.respond(request("/whoami", None)).await;
+        assert_eq!(1, store.len());
+        assert!(store.load(&id).await.unwrap().is_some());
+    }
+
+    #[test]
+    fn insert_unserializable_value() {
+        let session = SessionHandle::new(None);
+        let mut value = HashMap::new();
+        value.insert(vec![1u8], 1);
+        let error = session.insert("map", value).unwrap_err();
+        assert_eq!(StatusCode::InternalServerError, error.status());
+        assert_eq!(Some("failed to serialize session value"), error.message());
+        assert!(std::error::Error::source(&error).is_some());
+    }
+
+    struct BrokenStore;
+
+    #[async_trait]
+    impl SessionStore for BrokenStore {
+        async fn load(&self, _id: &str) -> io::Result<Option<SessionRecord>> {
+            Err(io::ErrorKind::NotFound.into())
+        }
+
+        async fn store(&self, _record: &SessionRecord) -> io::Result<()> {
+            Err(io::ErrorKind::PermissionDenied.into())
+        }
+
+        async fn destroy(&self, _id: &str) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn store_error() {
+        let server = ServerBuilder::new()
+            .route("/login", Method::Get, login)
+            .with(Session::new(BrokenStore))
+            .build();
+        let response = server.respond(request("/login", None)).await;
+        assert_eq!(StatusCode::InternalServerError, response.status_code());
+        let error = response.extension::<Error>().unwrap();
+        assert_eq!(Some("failed to save the session"), error.message());
+        assert!(std::error::Error::source(error).is_some());
+
+        let response = server
+            .respond(request("/login", Some(&generate_id())))
+            .await;
+        assert_eq!(StatusCode::InternalServerError, response.status_code());
+    }
+
+    #[tokio::test]
+    async fn ignore_invalid_id() {
+        let store = MemoryStore::new();
+        let server = server(Session::new(store.clone()));
+        let response = server
+            .respond(request("/whoami", Some("../../etc/passwd")))
+            .await;
+        assert_eq!(StatusCode::Ok, response.status_code());
+        assert!(!is_valid_id("../../etc/passwd"));
+        assert!(is_valid_id(&generate_id()));
+    }
+}
@@ -0,0 +1,175 @@
+use super::{generate_id, is_valid_id, SessionRecord, SessionStore};
+use async_trait::async_trait;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::fs;
+
+/// `SessionStore` which saves each session as a JSON file in a directory, so that sessions
+/// survive restarts of the server.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// Create a store saving sessions under `dir`. The directory is created if it does not exist.
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn path(&self, id: &str) -> io::Result<PathBuf> {
+        // IDs are checked by the middleware, but never let them escape from the directory.
+        if !is_valid_id(id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid session ID",
+            ));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
+
+#[async_trait]
+impl SessionStore for FileStore {
+    async fn load(&self, id: &str) -> io::Result<Option<SessionRecord>> {
+        let content = match fs::read(self.path(id)?).await {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        // A broken file is regarded as a missing session.
+        Ok(serde_json::from_slice(&content).ok())
+    }
+
+    async fn store(&self, record: &SessionRecord) -> io::Result<()> {
+        let path = self.path(&record.id)?;
+        fs::create_dir_all(&self.dir).await?;
+        let content = serde_json::to_vec(record)?;
+        // Write to a temporary file and rename it so that a partially written file is never read.
+        // Each write has its own temporary file because requests with the same session can be
+        // handled concurrently.
+        let tmp_path = self
+            .dir
+            .join(format!("{}.{}.tmp", record.id, generate_id()));
+        fs::write(&tmp_path, content).await?;
+        let renamed = fs::rename(&tmp_path, path).await;
+        if renamed.is_err() {
+            let _ = fs::remove_file(&tmp_path).await;
+        }
+        renamed
+    }
+
+    async fn destroy(&self, id: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(id)?).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Delete files of expired sessions. Broken files are deleted too because they are never
+    /// loaded.
+    async fn cleanup(&self, now: SystemTime) -> io::Result<()> {
+        let mut entries = match fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+            let is_expired = match fs::read(&path).await {
+                Ok(content) => serde_json::from_slice::<SessionRecord>(&content)
+                    .map_or(true, |record| record.expires_at <= now),
+                // Deleted by another request.
+                Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+                Err(err) => return Err(err),
+            };
+            if is_expired {
+                match fs::remove_file(&path).await {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => (),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::session::generate_id;
+
+    #[tokio::test]
+    async fn store_and_load() {
+        let dir = std::env::temp_dir().join(format!("qz-session-{}", generate_id()));
+        let store = FileStore::new(&dir);
+        let mut record = SessionRecord::new();
+        record.id = generate_id();
+        record
+            .values
+            .insert("user".to_string(), serde_json::json!("alice"));
+
+        assert_eq!(None, store.load(&record.id).await.unwrap());
+        store.store(&record).await.unwrap();
+        assert_eq!(Some(record.clone()), store.load(&record.id).await.unwrap());
+        store.destroy(&record.id).await.unwrap();
+        assert_eq!(None, store.load(&record.id).await.unwrap());
+        store.destroy(&record.id).await.unwrap();
+
+        assert!(store.load("../secret").await.is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn store_concurrently() {
+        let dir = std::env::temp_dir().join(format!("qz-session-{}", generate_id()));
+        let store = FileStore::new(&dir);
+        let mut record = SessionRecord::new();
+        record.id = generate_id();
+        let tasks = (0..16)
+            .map(|i| {
+                let store = store.clone();
+                let mut record = record.clone();
+                record.values.insert("i".to_string(), serde_json::json!(i));
+                tokio::spawn(async move { store.store(&record).await })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        let loaded = store.load(&record.id).await.unwrap().unwrap();
+        assert!(loaded.values["i"].as_u64().is_some_and(|i| i < 16));
+        // No temporary file is left.
+        assert_eq!(1, std::fs::read_dir(&dir).unwrap().count());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn cleanup() {
+        let dir = std::env::temp_dir().join(format!("qz-session-{}", generate_id()));
+        let store = FileStore::new(&dir);
+        let now = SystemTime::now();
+        let mut expired = SessionRecord::new();
+        expired.id = generate_id();
+        expired.expires_at = now;
+        let mut alive = SessionRecord::new();
+        alive.id = generate_id();
+        alive.expires_at = now + std::time::Duration::from_secs(1);
+        store.store(&expired).await.unwrap();
+        store.store(&alive).await.unwrap();
+
+        store.cleanup(now).await.unwrap();
+        assert_eq!(None, store.load(&expired.id).await.unwrap());
+        assert_eq!(Some(alive.clone()), store.load(&alive.id).await.unwrap());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
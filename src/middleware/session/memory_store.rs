@@ -0,0 +1,78 @@
+use super::{SessionRecord, SessionStore};
+use async_trait::async_trait;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// `SessionStore` which keeps sessions in memory. Sessions are lost when the server stops.
+/// Clones share the same sessions.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore {
+    sessions: Arc<Mutex<HashMap<String, SessionRecord>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn load(&self, id: &str) -> io::Result<Option<SessionRecord>> {
+        Ok(self.sessions.lock().unwrap().get(id).cloned())
+    }
+
+    async fn store(&self, record: &SessionRecord) -> io::Result<()> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(record.id.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn destroy(&self, id: &str) -> io::Result<()> {
+        self.sessions.lock().unwrap().remove(id);
+        Ok(())
+    }
+
+    async fn cleanup(&self, now: SystemTime) -> io::Result<()> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .retain(|_, record| record.expires_at > now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn cleanup() {
+        let store = MemoryStore::new();
+        let now = SystemTime::now();
+        for (id, expires_at) in [("expired", now), ("alive", now + Duration::from_secs(1))] {
+            let mut record = SessionRecord::new();
+            record.id = id.to_string();
+            record.expires_at = expires_at;
+            store.store(&record).await.unwrap();
+        }
+        store.cleanup(now).await.unwrap();
+        assert_eq!(1, store.len());
+        assert!(store.load("alive").await.unwrap().is_some());
+    }
+}
@@ -7,6 +7,12 @@ use crate::{
 use std::convert::TryFrom;
 use std::str;
 
+/// Check if a byte is allowed in a token(RFC 7230 section 3.2.6), such as a method and a header
+/// name.
+pub(crate) fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
 #[derive(Debug)]
 pub struct Parser<'a> {
     // Assume that Parser parses ASCII.
@@ -43,14 +49,14 @@ impl<'a> Parser<'a> {
     }
 
     /// Consume a first element and return error if it does not equal to `target`.
-    pub fn expect(&mut self, target: u8, error: StatusCode) -> crate::Result<()> {
+    pub fn expect(&mut self, target: u8, error: StatusCode) -> crate::Result<(), StatusCode> {
         match self.consume() {
             Some(b) if b == target => Ok(()),
             _ => Err(error),
         }
     }
 
-    pub fn parse_request_line(&mut self) -> crate::Result<(Method, Uri, Version)> {
+    pub fn parse_request_line(&mut self) -> crate::Result<(Method, Uri, Version), StatusCode> {
         let method = self.parse_method()?;
         let uri = self.parse_uri()?;
         let version = self.parse_version()?;
@@ -58,23 +64,25 @@ impl<'a> Parser<'a> {
         Ok((method, uri, version))
     }
 
-    fn parse_method(&mut self) -> crate::Result<Method> {
+    fn parse_method(&mut self) -> crate::Result<Method, StatusCode> {
         match self.read_until_whitespace() {
             Some(method) => Method::try_from(method),
             None => Err(StatusCode::BadRequest),
         }
     }
 
-    fn parse_uri(&mut self) -> crate::Result<Uri> {
+    fn parse_uri(&mut self) -> crate::Result<Uri, StatusCode> {
         let uri = self.read_until_whitespace().ok_or(StatusCode::BadRequest)?;
-        if uri.starts_with(&[b'/']) {
-            Ok(Uri::new(uri))
-        } else {
-            Err(StatusCode::BadRequest)
+        if !uri.starts_with(b"/") {
+            return Err(StatusCode::BadRequest);
         }
+        let uri = Uri::new(uri);
+        // Reject a path which cannot be decoded here rather than when it is routed.
+        uri.decoded_path()?;
+        Ok(uri)
     }
 
-    fn parse_version(&mut self) -> crate::Result<Version> {
+    fn parse_version(&mut self) -> crate::Result<Version, StatusCode> {
         let protocol = self
             .read_until(b'/')
             .ok_or(StatusCode::HttpVersionNotSupported)?;
@@ -88,12 +96,9 @@ impl<'a> Parser<'a> {
         Version::try_from(version)
     }
 
-    pub fn parse_header(&mut self) -> crate::Result<(HeaderName, HeaderValue)> {
-        let header_name = self
-            .read_until(b':')
-            .ok_or(StatusCode::BadRequest)?
-            .to_vec();
-        let header_name = HeaderName::from(header_name);
+    pub fn parse_header(&mut self) -> crate::Result<(HeaderName, HeaderValue), StatusCode> {
+        let header_name = self.read_until(b':').ok_or(StatusCode::BadRequest)?;
+        let header_name = HeaderName::try_from(header_name.to_vec())?;
         self.expect(b' ', StatusCode::BadRequest)?;
         let header_value = self
             .read_until(b'\r')
@@ -103,7 +108,25 @@ impl<'a> Parser<'a> {
         Ok((header_name, header_value))
     }
 
-    pub fn parse_body(&mut self, body_len: usize) -> crate::Result<Vec<u8>> {
+    /// Parse a line of chunk size in chunked transfer coding. Chunk extensions are ignored.
+    pub fn parse_chunk_size(&mut self) -> crate::Result<usize, StatusCode> {
+        let line = self.read_until(b'\r').ok_or(StatusCode::BadRequest)?;
+        let size = match line.iter().position(|&b| b == b';') {
+            Some(index) => &line[..index],
+            None => line,
+        };
+        let size = str::from_utf8(size)
+            .or(Err(StatusCode::BadRequest))?
+            .trim_end_matches([' ', '\t']);
+        if size.is_empty() || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(StatusCode::BadRequest);
+        }
+        let size = usize::from_str_radix(size, 16).or(Err(StatusCode::BadRequest))?;
+        self.expect(b'\n', StatusCode::BadRequest)?;
+        Ok(size)
+    }
+
+    pub fn parse_body(&mut self, body_len: usize) -> crate::Result<Vec<u8>, StatusCode> {
         if body_len > self.state.len() {
             return Err(StatusCode::LengthRequired);
         }
@@ -162,6 +185,22 @@ mod tests {
         assert_eq!(Ok(Uri::new(b"/index.html")), p.parse_uri());
     }
 
+    #[test]
+    fn parse_uri_with_query() {
+        let bytes = "/search?q=qz HTTP/1.1\r\n".as_bytes();
+        let mut p = Parser::new(bytes);
+        let uri = p.parse_uri().unwrap();
+        assert_eq!(b"/search", uri.path());
+        assert_eq!(Some(&b"q=qz"[..]), uri.query());
+    }
+
+    #[test]
+    fn parse_uri_with_encoded_slash() {
+        let bytes = "/a%2Fb HTTP/1.1\r\n".as_bytes();
+        let mut p = Parser::new(bytes);
+        assert_eq!(Err(StatusCode::BadRequest), p.parse_uri());
+    }
+
     #[test]
     fn parse_version() {
         let bytes = "HTTP/1.1\r\n".as_bytes();
@@ -176,6 +215,29 @@ mod tests {
         assert_eq!(Ok((HeaderName::Accept, b"*/*".to_vec())), p.parse_header());
     }
 
+    #[test]
+    fn parse_custom_header() {
+        let bytes = b"X-Request-Id: 42\r\n";
+        let mut p = Parser::new(bytes);
+        assert_eq!(
+            Ok((
+                HeaderName::try_from("x-request-id").unwrap(),
+                b"42".to_vec()
+            )),
+            p.parse_header()
+        );
+        let mut p = Parser::new(b"X-Request-Id : 42\r\n");
+        assert_eq!(Err(StatusCode::BadRequest), p.parse_header());
+    }
+
+    #[test]
+    fn parse_chunk_size() {
+        let mut p = Parser::new(b"1aF;name=value\r\n");
+        assert_eq!(Ok(0x1af), p.parse_chunk_size());
+        let mut p = Parser::new(b"-1\r\n");
+        assert_eq!(Err(StatusCode::BadRequest), p.parse_chunk_size());
+    }
+
     #[test]
     fn parse_body() {
         let bytes = b"Hello, World!";
@@ -51,7 +51,7 @@ where
     async fn call(&self, _request: Request, _state: State) -> crate::Result<Response> {
         let response = Response::builder()
             .set_status_code(self.status_code)
-            .set_header(HeaderName::Location, self.uri.0.clone())
+            .set_header(HeaderName::Location, self.uri.to_bytes())
             .build();
         Ok(response)
     }
@@ -67,7 +67,7 @@ impl From<&Redirect> for Response {
     fn from(redirect: &Redirect) -> Self {
         Response::builder()
             .set_status_code(redirect.status_code)
-            .set_header(HeaderName::Location, redirect.uri.as_ref())
+            .set_header(HeaderName::Location, redirect.uri.to_bytes())
             .build()
     }
 }
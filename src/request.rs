@@ -2,11 +2,16 @@ use serde::de::DeserializeOwned;
 
 use crate::{
     body::Body,
-    header::{HeaderName, HeaderValue},
+    cookie::CookieJar,
+    header::{
+        typed::{self, TypedHeader},
+        HeaderMap, HeaderName, HeaderValue,
+    },
     method::Method,
+    middleware::SessionHandle,
     parser::Parser,
     status::StatusCode,
-    Uri, Version,
+    Extensions, Uri, Version,
 };
 use std::{collections::HashMap, fmt, str};
 
@@ -36,8 +41,20 @@ impl RequestBuilder {
         self.inner.headers.get(&name)
     }
 
+    /// Set a header replacing existing values of it.
     pub fn set_header(mut self, name: HeaderName, value: impl Into<HeaderValue>) -> Self {
-        self.inner.headers.insert(name, value.into());
+        self.inner.headers.insert(name, value);
+        self
+    }
+
+    /// Add a header keeping existing values of it.
+    pub fn append_header(mut self, name: HeaderName, value: impl Into<HeaderValue>) -> Self {
+        self.inner.headers.append(name, value);
+        self
+    }
+
+    pub fn typed_header<H: TypedHeader>(mut self, header: H) -> Self {
+        self.inner.headers.typed_insert(&header);
         self
     }
 
@@ -46,7 +63,7 @@ impl RequestBuilder {
         self
     }
 
-    fn parse_request_line(&mut self, bytes: &[u8]) -> crate::Result<()> {
+    fn parse_request_line(&mut self, bytes: &[u8]) -> crate::Result<(), StatusCode> {
         let mut p = Parser::new(bytes);
         let (method, uri, version) = p.parse_request_line()?;
         self.inner.method = method;
@@ -55,22 +72,58 @@ impl RequestBuilder {
         Ok(())
     }
 
-    fn parse_header(&mut self, bytes: &[u8]) -> crate::Result<()> {
+    fn parse_header(&mut self, bytes: &[u8]) -> crate::Result<(), StatusCode> {
         let mut p = Parser::new(bytes);
         let (name, value) = p.parse_header()?;
-        self.inner.headers.insert(name, value);
+        self.inner.headers.append(name, value);
         Ok(())
     }
 
-    fn parse_body(&mut self, bytes: &[u8]) -> crate::Result<()> {
-        let body_len = std::str::from_utf8(
-            self.inner
-                .get_header(HeaderName::ContentLength)
-                .ok_or(StatusCode::LengthRequired)?,
-        )
-        .or(Err(StatusCode::LengthRequired))?
-        .parse::<usize>()
-        .or(Err(StatusCode::LengthRequired))?;
+    /// Check if the body is encoded with chunked transfer coding.
+    /// A request with both `Content-Length` and `Transfer-Encoding` is rejected because the
+    /// ambiguity of its length can be used to smuggle requests.
+    fn is_chunked(&self) -> crate::Result<bool, StatusCode> {
+        let headers = &self.inner.headers;
+        if !headers.contains_key(&HeaderName::TransferEncoding) {
+            return Ok(false);
+        }
+        if headers.contains_key(&HeaderName::ContentLength) {
+            return Err(StatusCode::BadRequest);
+        }
+        // Only chunked transfer coding is supported. Codings can be split into multiple lines.
+        let codings = headers
+            .get_all(&HeaderName::TransferEncoding)
+            .flat_map(|value| value.split(|&b| b == b','))
+            .map(|coding| coding.trim_ascii())
+            .collect::<Vec<_>>();
+        match codings[..] {
+            [coding] if coding.eq_ignore_ascii_case(b"chunked") => Ok(true),
+            _ => Err(StatusCode::NotImplemented),
+        }
+    }
+
+    /// Length of the body declared by `Content-Length`. A request without the header has no body.
+    /// Multiple `Content-Length` with different values are rejected.
+    fn content_length(&self) -> crate::Result<usize, StatusCode> {
+        let mut lengths = self.inner.headers.get_all(&HeaderName::ContentLength);
+        let length = match lengths.next() {
+            Some(length) => length,
+            None => return Ok(0),
+        };
+        if lengths.any(|other| other != length) {
+            return Err(StatusCode::BadRequest);
+        }
+        // `parse()` accepts a sign, which must not be taken as a part of the length.
+        if length.is_empty() || !length.iter().all(u8::is_ascii_digit) {
+            return Err(StatusCode::BadRequest);
+        }
+        std::str::from_utf8(length)
+            .or(Err(StatusCode::BadRequest))?
+            .parse::<usize>()
+            .or(Err(StatusCode::BadRequest))
+    }
+
+    fn parse_body(&mut self, bytes: &[u8], body_len: usize) -> crate::Result<(), StatusCode> {
         let mut p = Parser::new(bytes);
         let body = p.parse_body(body_len)?;
         self.inner.set_body(body);
@@ -88,8 +141,10 @@ pub struct Request {
     pub(crate) method: Method,
     pub(crate) uri: Uri,
     pub(crate) version: Version,
-    pub(crate) headers: HashMap<HeaderName, HeaderValue>,
+    pub(crate) headers: HeaderMap,
     pub(crate) body: Body,
+    pub(crate) params: HashMap<String, String>,
+    pub(crate) extensions: Extensions,
 }
 
 impl Request {
@@ -97,8 +152,8 @@ impl Request {
         RequestBuilder::default()
     }
 
-    pub fn method(&self) -> Method {
-        self.method
+    pub fn method(&self) -> &Method {
+        &self.method
     }
 
     pub fn uri(&self) -> &Uri {
@@ -109,26 +164,102 @@ impl Request {
         &self.version
     }
 
-    pub fn headers(&self) -> &HashMap<HeaderName, HeaderValue> {
+    pub fn headers(&self) -> &HeaderMap {
         &self.headers
     }
 
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// First value of the header. Use `headers().get_all()` to get all of the values.
     pub fn get_header(&self, name: HeaderName) -> Option<&HeaderValue> {
         self.headers.get(&name)
     }
 
+    /// Set a header replacing existing values of it.
     pub fn set_header(&mut self, name: HeaderName, value: impl Into<HeaderValue>) {
-        self.headers.insert(name, value.into());
+        self.headers.insert(name, value);
+    }
+
+    /// Parse a header as `H`, e.g. `request.typed_header::<Authorization>()`.
+    /// `None` is returned if the header is missing or malformed.
+    pub fn typed_header<H: TypedHeader>(&self) -> Option<H> {
+        self.headers.typed_get()
+    }
+
+    /// Cookies sent with `Cookie` header. Pass the jar to `Response::set_cookies()` after
+    /// modifying it to send the changes.
+    pub fn cookies(&self) -> CookieJar {
+        self.typed_header::<typed::Cookie>()
+            .map(CookieJar::from)
+            .unwrap_or_default()
+    }
+
+    /// Session of the client. `None` is returned unless `Session` middleware is used.
+    pub fn session(&self) -> Option<&SessionHandle> {
+        self.extension()
+    }
+
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    /// Value of type `T` inserted by middleware, e.g. `request.extension::<AuthenticatedUser>()`.
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get()
+    }
+
+    /// Insert a value to pass it to following middlewares and the handler.
+    pub fn insert_extension<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions.insert(value)
     }
 
     pub fn body(&self) -> &Body {
         &self.body
     }
 
+    /// Value of a named parameter or a wildcard in the route matched with this request.
+    /// e.g. `request.param("id")` returns `Some("42")` for the route `/users/:id` and
+    /// URI `/users/42`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+
+    pub fn params(&self) -> &HashMap<String, String> {
+        &self.params
+    }
+
     pub fn set_body(&mut self, body: impl Into<Body>) {
         self.body = body.into();
     }
 
+    /// Parse query string of the URI. A request without query string is regarded as empty one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use qz::request::Request;
+    /// use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     page: Option<u32>,
+    /// }
+    ///
+    /// let request = Request::builder().set_uri("/search?q=qz&page=2").build();
+    /// let search: Search = request.query().unwrap();
+    /// assert_eq!("qz", &search.q);
+    /// assert_eq!(Some(2), search.page);
+    /// ```
+    pub fn query<T: DeserializeOwned>(&self) -> crate::Result<T> {
+        let query = self.uri.query().unwrap_or_default();
+        Ok(serde_urlencoded::from_bytes::<T>(query)?)
+    }
+
     pub fn body_form<T: DeserializeOwned>(&self) -> crate::Result<T> {
         self.body.into_form()
     }
@@ -142,7 +273,7 @@ impl fmt::Display for Request {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{} {} HTTP/{}", self.method, self.uri, self.version)?;
         for (name, value) in self.headers.iter() {
-            writeln!(f, "{}: {}", name, str::from_utf8(&value).unwrap())?;
+            writeln!(f, "{}: {}", name, str::from_utf8(value).unwrap())?;
         }
         writeln!(f, "{}", self.body())?;
         Ok(())
@@ -153,6 +284,14 @@ impl fmt::Display for Request {
 pub enum ParseState {
     RequestLine,
     Headers,
+    /// Waiting for the rest of the body of given length.
+    Body(usize),
+    /// Waiting for a line of chunk size in a chunked body.
+    ChunkSize,
+    /// Waiting for the rest of a chunk of given size and CRLF following it.
+    ChunkData(usize),
+    /// Waiting for trailer fields after the last chunk.
+    Trailers,
     Completed,
 }
 
@@ -167,74 +306,164 @@ pub enum ParseState {
 /// because it is hard to know when to finish reading from socket.
 /// So this struct parse a request with second strategy to get body size from `Content-Length`
 /// header while parsing.
+/// A body is accumulated over multiple calls of `try_parse()` until whole of it arrives.
+/// A body with `Transfer-Encoding: chunked` is decoded chunk by chunk.
 pub struct RequestBuffer {
     buffer: Vec<u8>,
     state: ParseState,
     builder: RequestBuilder,
+    chunked_body: Vec<u8>,
+    max_body_size: usize,
+}
+
+impl Default for RequestBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl RequestBuffer {
+    /// Default limit of a request body size, 8 MiB.
+    pub const DEFAULT_MAX_BODY_SIZE: usize = 8 * 1024 * 1024;
+
     pub fn new() -> Self {
+        Self::with_max_body_size(Self::DEFAULT_MAX_BODY_SIZE)
+    }
+
+    /// Create a buffer which rejects a request whose body is larger than `max_body_size` bytes
+    /// with `413 Payload Too Large`.
+    pub fn with_max_body_size(max_body_size: usize) -> Self {
         Self {
             buffer: Vec::new(),
             state: ParseState::RequestLine,
             builder: RequestBuilder::new(),
+            chunked_body: Vec::new(),
+            max_body_size,
         }
     }
 
-    pub fn complete(self) -> Request {
-        self.builder.build()
+    /// Take the parsed request out and get ready to parse a next request.
+    /// Bytes following the request, which belong to pipelined requests, are kept in the buffer
+    /// and parsed by a next call of `try_parse()`.
+    pub fn complete(&mut self) -> Request {
+        self.state = ParseState::RequestLine;
+        std::mem::take(&mut self.builder).build()
+    }
+
+    /// Whether no byte of a next request has arrived yet.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.state == ParseState::RequestLine && self.buffer.is_empty()
     }
 
     /// Extend buffer of this struct with `data` and try to parse given request data.
-    pub fn try_parse(&mut self, data: &[u8]) -> crate::Result<ParseState> {
-        self.buffer.extend_from_slice(&data);
-        let mut buf_iter = self.buffer.iter();
+    pub fn try_parse(&mut self, data: &[u8]) -> crate::Result<ParseState, StatusCode> {
+        self.buffer.extend_from_slice(data);
         let mut parse_start = 0;
-        let mut parse_end = 0;
         // self.buffer may contain multiple lines(multiple CRLFs).
         loop {
-            if let ParseState::Completed = self.state {
-                return Ok(ParseState::Completed);
-            }
-
-            // Find "\r\n" to determine a line.
-            if let Some(dist_to_crlf) = buf_iter.position(|&b| b == b'\r') {
-                if let Some(b'\n') = buf_iter.next() {
-                    parse_end += dist_to_crlf + 2;
-                    match self.state {
-                        ParseState::RequestLine => {
-                            self.builder
-                                .parse_request_line(&self.buffer[parse_start..parse_end])?;
-                            self.state = ParseState::Headers;
+            match self.state {
+                ParseState::RequestLine | ParseState::Headers => {
+                    // Find "\r\n" to determine a line.
+                    let dist_to_crlf = match find_crlf(&self.buffer[parse_start..]) {
+                        Some(dist) => dist,
+                        None => break,
+                    };
+                    let parse_end = parse_start + dist_to_crlf + 2;
+                    let line = &self.buffer[parse_start..parse_end];
+                    parse_start = parse_end;
+                    if let ParseState::RequestLine = self.state {
+                        self.builder.parse_request_line(line)?;
+                        self.state = ParseState::Headers;
+                    } else if dist_to_crlf == 0 {
+                        // CRLF only
+                        if self.builder.is_chunked()? {
+                            self.state = ParseState::ChunkSize;
+                            continue;
                         }
-                        ParseState::Headers => {
-                            if dist_to_crlf == 0 {
-                                // CRLF only
-                                if self.builder.get_header(HeaderName::ContentLength).is_some() {
-                                    self.builder.parse_body(&self.buffer[(parse_start + 2)..])?;
-                                }
-                                self.state = ParseState::Completed;
-                            } else {
-                                self.builder
-                                    .parse_header(&self.buffer[parse_start..parse_end])?;
-                            }
+                        let body_len = self.builder.content_length()?;
+                        if body_len > self.max_body_size {
+                            return Err(StatusCode::PayloadTooLarge);
                         }
-                        _ => unreachable!(),
+                        self.state = if body_len == 0 {
+                            ParseState::Completed
+                        } else {
+                            ParseState::Body(body_len)
+                        };
+                    } else {
+                        self.builder.parse_header(line)?;
+                    }
+                }
+                ParseState::Body(body_len) => {
+                    let remaining = &self.buffer[parse_start..];
+                    if remaining.len() < body_len {
+                        break;
+                    }
+                    self.builder.parse_body(remaining, body_len)?;
+                    parse_start += body_len;
+                    self.state = ParseState::Completed;
+                }
+                ParseState::ChunkSize => {
+                    let dist_to_crlf = match find_crlf(&self.buffer[parse_start..]) {
+                        Some(dist) => dist,
+                        None => break,
+                    };
+                    let parse_end = parse_start + dist_to_crlf + 2;
+                    let mut p = Parser::new(&self.buffer[parse_start..parse_end]);
+                    let chunk_size = p.parse_chunk_size()?;
+                    parse_start = parse_end;
+                    self.state = if chunk_size == 0 {
+                        ParseState::Trailers
+                    } else if chunk_size > self.max_body_size - self.chunked_body.len() {
+                        return Err(StatusCode::PayloadTooLarge);
+                    } else {
+                        ParseState::ChunkData(chunk_size)
+                    };
+                }
+                ParseState::ChunkData(chunk_size) => {
+                    let remaining = &self.buffer[parse_start..];
+                    // Not `chunk_size + 2`, which may overflow with a huge chunk size.
+                    if remaining.len().saturating_sub(chunk_size) < 2 {
+                        break;
+                    }
+                    let (chunk, tail) = remaining.split_at(chunk_size);
+                    if !tail.starts_with(b"\r\n") {
+                        return Err(StatusCode::BadRequest);
                     }
+                    self.chunked_body.extend_from_slice(chunk);
+                    parse_start += chunk_size + 2;
+                    self.state = ParseState::ChunkSize;
+                }
+                ParseState::Trailers => {
+                    let dist_to_crlf = match find_crlf(&self.buffer[parse_start..]) {
+                        Some(dist) => dist,
+                        None => break,
+                    };
+                    let parse_end = parse_start + dist_to_crlf + 2;
+                    let line = &self.buffer[parse_start..parse_end];
                     parse_start = parse_end;
-                } else {
-                    break;
+                    if dist_to_crlf == 0 {
+                        let body = std::mem::take(&mut self.chunked_body);
+                        self.builder.inner.set_body(body);
+                        self.state = ParseState::Completed;
+                    } else {
+                        // Trailer fields are discarded. Merging them into headers would let a
+                        // client override headers which have already been checked.
+                        Parser::new(line).parse_header()?;
+                    }
                 }
-            } else {
-                break;
+                ParseState::Completed => break,
             }
         }
-        self.buffer = self.buffer.drain(parse_end..).collect();
+        self.buffer.drain(..parse_start);
         Ok(self.state)
     }
 }
 
+/// Return distance to the first CRLF in `bytes`.
+fn find_crlf(bytes: &[u8]) -> Option<usize> {
+    bytes.windows(2).position(|w| w == b"\r\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +516,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn request_cookies() {
+        let request = Request::builder()
+            .set_header(HeaderName::Cookie, "id=42; theme=dark")
+            .build();
+        let jar = request.cookies();
+        assert_eq!(Some("42"), jar.get("id"));
+        assert_eq!(Some("dark"), jar.get("theme"));
+        assert_eq!(None, Request::default().cookies().get("id"));
+    }
+
+    #[test]
+    fn build_request_with_repeated_headers() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Ok(ParseState::Completed),
+            request_buf.try_parse(
+                b"GET / HTTP/1.1\r\nAccept: text/html\r\nHost: localhost\r\nAccept: */*\r\n\r\n"
+            )
+        );
+        let request = request_buf.complete();
+        assert_eq!(
+            vec![&b"text/html".to_vec(), &b"*/*".to_vec()],
+            request
+                .headers()
+                .get_all(&HeaderName::Accept)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(&b"text/html".to_vec()),
+            request.get_header(HeaderName::Accept)
+        );
+    }
+
     #[test]
     fn build_request_with_large_chunk() {
         let data = b"GET /~/index.html HTTP/1.1\r\nAccept: */*\r\nHost: localhost:8080\r\nUser-Agent: curl\r\n"
@@ -337,4 +600,170 @@ mod tests {
             request_buf.complete()
         );
     }
+
+    #[test]
+    fn build_pipelined_requests() {
+        let data = b"GET /a HTTP/1.1\r\n\r\nPOST /b HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloGET /c HTTP/1.1\r\n";
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(Ok(ParseState::Completed), request_buf.try_parse(data));
+        assert_eq!(Uri::new(b"/a"), request_buf.complete().uri);
+
+        assert_eq!(Ok(ParseState::Completed), request_buf.try_parse(&[]));
+        let request = request_buf.complete();
+        assert_eq!(Uri::new(b"/b"), request.uri);
+        assert_eq!(&Body::from("Hello"), request.body());
+
+        assert_eq!(Ok(ParseState::Headers), request_buf.try_parse(&[]));
+        assert_eq!(Ok(ParseState::Completed), request_buf.try_parse(b"\r\n"));
+        assert_eq!(Uri::new(b"/c"), request_buf.complete().uri);
+    }
+
+    #[test]
+    fn build_request_with_body_across_reads() {
+        let body = "Hello, World!".repeat(1000);
+        let data = format!(
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let mut request_buf = RequestBuffer::new();
+        let mut chunks = data.as_bytes().chunks(4096);
+        loop {
+            match request_buf.try_parse(chunks.next().unwrap()) {
+                Ok(ParseState::Completed) => break,
+                Ok(_) => continue,
+                Err(err) => panic!("{:?}", err),
+            }
+        }
+        assert_eq!(None, chunks.next());
+        assert_eq!(&Body::from(body), request_buf.complete().body());
+    }
+
+    #[test]
+    fn too_large_body() {
+        let mut request_buf = RequestBuffer::with_max_body_size(4);
+        assert_eq!(
+            Err(StatusCode::PayloadTooLarge),
+            request_buf.try_parse(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n")
+        );
+    }
+
+    #[test]
+    fn build_request_with_chunked_body() {
+        let data = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+            7\r\nHello, \r\n6;name=value\r\nWorld!\r\n0\r\nExpires: never\r\n\r\n"
+            .chunks(5)
+            .map(|c| c.to_vec())
+            .collect::<Vec<_>>();
+        let mut request_buf = RequestBuffer::new();
+        for message in data {
+            match request_buf.try_parse(&message) {
+                Ok(ParseState::Completed) => break,
+                Ok(_) => continue,
+                Err(err) => panic!("{:?}", err),
+            }
+        }
+        let request = request_buf.complete();
+        assert_eq!(&Body::from("Hello, World!"), request.body());
+        assert_eq!(None, request.get_header(HeaderName::Expires));
+    }
+
+    #[test]
+    fn chunked_body_followed_by_pipelined_request() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Ok(ParseState::Completed),
+            request_buf.try_parse(
+                b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                5\r\nHello\r\n0\r\n\r\nGET / HTTP/1.1\r\n\r\n"
+            )
+        );
+        assert_eq!(&Body::from("Hello"), request_buf.complete().body());
+        assert_eq!(Ok(ParseState::Completed), request_buf.try_parse(&[]));
+    }
+
+    #[test]
+    fn reject_both_content_length_and_transfer_encoding() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Err(StatusCode::BadRequest),
+            request_buf.try_parse(
+                b"POST / HTTP/1.1\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn reject_different_content_lengths() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Err(StatusCode::BadRequest),
+            request_buf
+                .try_parse(b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 3\r\n\r\n")
+        );
+    }
+
+    #[test]
+    fn reject_signed_content_length() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Err(StatusCode::BadRequest),
+            request_buf.try_parse(b"POST / HTTP/1.1\r\nContent-Length: +5\r\n\r\nHello")
+        );
+    }
+
+    #[test]
+    fn reject_invalid_chunk() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Err(StatusCode::BadRequest),
+            request_buf
+                .try_parse(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nHello\r\n")
+        );
+    }
+
+    #[test]
+    fn too_large_chunked_body() {
+        let mut request_buf = RequestBuffer::with_max_body_size(8);
+        assert_eq!(
+            Err(StatusCode::PayloadTooLarge),
+            request_buf.try_parse(
+                b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n5\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn too_large_chunk_size() {
+        let mut request_buf = RequestBuffer::new();
+        let request = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\n{:x}\r\n",
+            usize::MAX
+        );
+        assert_eq!(
+            Err(StatusCode::PayloadTooLarge),
+            request_buf.try_parse(request.as_bytes())
+        );
+    }
+
+    #[test]
+    fn discard_trailers() {
+        let mut request_buf = RequestBuffer::new();
+        assert_eq!(
+            Ok(ParseState::Completed),
+            request_buf.try_parse(
+                b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Type: text/plain\r\n\r\n\
+                5\r\nHello\r\n0\r\nAuthorization: Basic YTpi\r\nContent-Type: application/json\r\n\r\n"
+            )
+        );
+        let request = request_buf.complete();
+        assert_eq!(None, request.get_header(HeaderName::Authorization));
+        assert_eq!(
+            vec![&b"text/plain".to_vec()],
+            request
+                .headers()
+                .get_all(&HeaderName::ContentType)
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,12 +1,18 @@
+mod into_response;
+
+pub use crate::extract::Json;
+pub use into_response::{Html, IntoResponse};
+
 use crate::{
-    body::Body,
-    header::{HeaderName, HeaderValue},
+    body::{Body, BodyStream},
+    cookie::{Cookie, CookieJar},
+    header::{typed::TypedHeader, HeaderMap, HeaderName, HeaderValue},
     mime,
     status::StatusCode,
-    Version,
+    Extensions, Version,
 };
-use std::{collections::HashMap, convert::From};
-use tokio::io::{self, AsyncWrite, AsyncWriteExt};
+use std::convert::From;
+use tokio::io::{self, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 /// Builder of `Response`.
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -26,8 +32,30 @@ impl ResponseBuilder {
         self
     }
 
+    /// Set a header replacing existing values of it.
     pub fn set_header(mut self, name: HeaderName, value: impl Into<HeaderValue>) -> Self {
-        self.inner.set_header(name, value.into());
+        self.inner.set_header(name, value);
+        self
+    }
+
+    /// Add a header keeping existing values of it, e.g. `Set-Cookie`.
+    pub fn append_header(mut self, name: HeaderName, value: impl Into<HeaderValue>) -> Self {
+        self.inner.append_header(name, value);
+        self
+    }
+
+    pub fn typed_header<H: TypedHeader>(mut self, header: H) -> Self {
+        self.inner.set_typed_header(&header);
+        self
+    }
+
+    pub fn set_cookie(mut self, cookie: Cookie) -> Self {
+        self.inner.set_cookie(cookie);
+        self
+    }
+
+    pub fn set_cookies(mut self, jar: &CookieJar) -> Self {
+        self.inner.set_cookies(jar);
         self
     }
 
@@ -46,6 +74,11 @@ impl ResponseBuilder {
         self
     }
 
+    pub fn extension<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.inner.insert_extension(value);
+        self
+    }
+
     pub fn build(self) -> Response {
         self.inner
     }
@@ -55,12 +88,15 @@ impl ResponseBuilder {
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct Response {
     pub(crate) status_code: StatusCode,
-    pub(crate) headers: HashMap<HeaderName, HeaderValue>,
+    pub(crate) headers: HeaderMap,
     pub(crate) version: Version,
     pub(crate) body: Body,
+    pub(crate) extensions: Extensions,
 }
 
 impl Response {
+    const STREAM_CHUNK_SIZE: usize = 8192;
+
     pub fn new(status_code: StatusCode) -> Self {
         Self {
             status_code,
@@ -80,16 +116,48 @@ impl Response {
         self.version
     }
 
-    pub fn headers(&self) -> &HashMap<HeaderName, HeaderValue> {
+    pub fn headers(&self) -> &HeaderMap {
         &self.headers
     }
 
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// First value of the header. Use `headers().get_all()` to get all of the values.
     pub fn get_header(&self, name: &HeaderName) -> Option<&HeaderValue> {
         self.headers.get(name)
     }
 
+    /// Set a header replacing existing values of it.
     pub fn set_header(&mut self, name: HeaderName, value: impl Into<HeaderValue>) {
-        self.headers.insert(name, value.into());
+        self.headers.insert(name, value);
+    }
+
+    /// Add a header keeping existing values of it, e.g. `Set-Cookie`.
+    pub fn append_header(&mut self, name: HeaderName, value: impl Into<HeaderValue>) {
+        self.headers.append(name, value);
+    }
+
+    /// Parse a header as `H`. `None` is returned if the header is missing or malformed.
+    pub fn typed_header<H: TypedHeader>(&self) -> Option<H> {
+        self.headers.typed_get()
+    }
+
+    pub fn set_typed_header<H: TypedHeader>(&mut self, header: &H) {
+        self.headers.typed_insert(header);
+    }
+
+    /// Add `Set-Cookie` header keeping other cookies.
+    pub fn set_cookie(&mut self, cookie: Cookie) {
+        self.append_header(HeaderName::SetCookie, cookie.to_string());
+    }
+
+    /// Add `Set-Cookie` headers for the cookies added to or removed from `jar`.
+    pub fn set_cookies(&mut self, jar: &CookieJar) {
+        for cookie in jar.delta() {
+            self.append_header(HeaderName::SetCookie, cookie.to_string());
+        }
     }
 
     pub fn set_content_length(&mut self, length: usize) {
@@ -102,16 +170,67 @@ impl Response {
             .insert(HeaderName::ContentType, mime_type.to_vec());
     }
 
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    /// Value of type `T` inserted by the handler or middleware. Extensions are not sent to the
+    /// client.
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get()
+    }
+
+    pub fn insert_extension<T: Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.extensions.insert(value)
+    }
+
     pub fn body(&self) -> &Body {
         &self.body
     }
 
+    /// Set a body and `Content-Length` of it. If the body is a stream of unknown length,
+    /// `Transfer-Encoding: chunked` is set instead.
     pub fn set_body(&mut self, bytes: impl Into<Body>) {
         self.body = bytes.into();
-        self.set_content_length(self.body.len());
+        match &self.body {
+            Body::Stream(stream) if stream.length().is_none() => {
+                self.headers.remove(&HeaderName::ContentLength);
+                self.set_header(HeaderName::TransferEncoding, "chunked");
+            }
+            _ => {
+                self.headers.remove(&HeaderName::TransferEncoding);
+                self.set_content_length(self.body.len());
+            }
+        }
     }
 
     pub async fn send<W>(&self, connection: &mut W) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.write_head(connection).await?;
+        match &self.body {
+            Body::Stream(stream) => Self::send_stream(stream, connection).await?,
+            body => connection.write_all(body.as_ref()).await?,
+        }
+        connection.flush().await
+    }
+
+    /// Send only a status line and headers, which is a response to `HEAD` request.
+    /// Headers such as `Content-Length` are the same as ones of `send()`.
+    pub async fn send_without_body<W>(&self, connection: &mut W) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.write_head(connection).await?;
+        connection.flush().await
+    }
+
+    async fn write_head<W>(&self, connection: &mut W) -> io::Result<()>
     where
         W: AsyncWrite + Unpin,
     {
@@ -128,21 +247,59 @@ impl Response {
             // Consider to use `AsyncWriteExt::write_vectored()`
             connection.write_all(name.as_ref()).await?;
             connection.write_all(b": ").await?;
-            connection.write_all(&value).await?;
+            connection.write_all(value).await?;
             connection.write_all(b"\r\n").await?;
         }
-        connection.write_all(b"\r\n").await?;
-        connection.write_all(self.body.as_ref()).await?;
-        connection.flush().await
+        if !self.headers.contains_key(&HeaderName::ContentLength)
+            && !self.headers.contains_key(&HeaderName::TransferEncoding)
+            && self.status_code != StatusCode::NoContent
+            && self.status_code != StatusCode::NotModified
+        {
+            // Without `Content-Length`, a client cannot know where the body ends on a persistent
+            // connection. `204 No Content` and `304 Not Modified` never have a body, so they do not
+            // need one.
+            connection.write_all(b"Content-Length: ").await?;
+            connection
+                .write_all(self.body.len().to_string().as_bytes())
+                .await?;
+            connection.write_all(b"\r\n").await?;
+        }
+        connection.write_all(b"\r\n").await
+    }
+
+    /// Write a streaming body as is if its length is known, or in chunked transfer coding.
+    async fn send_stream<W>(stream: &BodyStream, connection: &mut W) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut reader = stream.lock().await;
+        if let Some(len) = stream.length() {
+            let written = io::copy(&mut reader.as_mut().take(len), connection).await?;
+            if written < len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            return Ok(());
+        }
+
+        let mut buf = vec![0; Self::STREAM_CHUNK_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            connection
+                .write_all(format!("{:X}\r\n", n).as_bytes())
+                .await?;
+            connection.write_all(&buf[..n]).await?;
+            connection.write_all(b"\r\n").await?;
+        }
+        connection.write_all(b"0\r\n\r\n").await
     }
 }
 
 impl From<crate::Result<Response>> for Response {
     fn from(res: crate::Result<Response>) -> Self {
-        match res {
-            Ok(res) => res,
-            Err(code) => code.into(),
-        }
+        res.into_response()
     }
 }
 
@@ -210,4 +367,73 @@ mod tests {
         assert_eq!(0, response.headers().len());
         assert_eq!(&Body::None, response.body());
     }
+
+    #[tokio::test]
+    async fn send_stream_with_len() {
+        let body = Body::from_reader_with_len(&b"Hello, World!"[..], 5);
+        let response = Response::from(body);
+        let mut sent = Vec::new();
+        response.send(&mut sent).await.unwrap();
+        assert_eq!(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello",
+            &sent[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_multiple_values() {
+        let response = Response::builder()
+            .set_header(HeaderName::ContentLength, "0")
+            .append_header(HeaderName::SetCookie, "a=1")
+            .append_header(HeaderName::SetCookie, "b=2")
+            .build();
+        let mut sent = Vec::new();
+        response.send(&mut sent).await.unwrap();
+        assert_eq!(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n",
+            &sent[..]
+        );
+    }
+
+    #[test]
+    fn set_cookies() {
+        let mut jar = CookieJar::new();
+        jar.add(Cookie::new("theme", "dark").set_path("/"));
+        let response = Response::builder()
+            .set_cookie(Cookie::new("id", "42").set_http_only(true))
+            .set_cookies(&jar)
+            .build();
+        let cookies = response
+            .headers()
+            .get_all(&HeaderName::SetCookie)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![&b"id=42; HttpOnly"[..], &b"theme=dark; Path=/"[..]],
+            cookies
+        );
+    }
+
+    #[tokio::test]
+    async fn send_without_body() {
+        let response = Response::from("Hello");
+        let mut sent = Vec::new();
+        response.send_without_body(&mut sent).await.unwrap();
+        let sent = String::from_utf8(sent).unwrap();
+        assert!(sent.contains("Content-Length: 5\r\n"));
+        assert!(sent.ends_with("\r\n\r\n"));
+    }
+
+    #[tokio::test]
+    async fn send_chunked_stream() {
+        let data = "a".repeat(Response::STREAM_CHUNK_SIZE + 1);
+        let body = Body::from_reader(io::BufReader::new(std::io::Cursor::new(data)));
+        let response = Response::from(body);
+        let mut sent = Vec::new();
+        response.send(&mut sent).await.unwrap();
+        let expected = format!(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2000\r\n{}\r\n1\r\na\r\n0\r\n\r\n",
+            "a".repeat(Response::STREAM_CHUNK_SIZE)
+        );
+        assert_eq!(expected.as_bytes(), &sent[..]);
+    }
 }
@@ -0,0 +1,251 @@
+use super::Response;
+use crate::{
+    body::Body,
+    extract::Json,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    mime,
+    redirect::Redirect,
+    status::StatusCode,
+    Error,
+};
+use serde::Serialize;
+
+/// Value which can be returned from handlers.
+///
+/// # Examples
+/// ```rust
+/// use qz::{
+///     header::HeaderName,
+///     response::{IntoResponse, Json},
+///     status::StatusCode,
+/// };
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     id: u32,
+/// }
+///
+/// let response = (
+///     StatusCode::Ok,
+///     [(HeaderName::CacheControl, "no-store")],
+///     Json(User { id: 42 }),
+/// )
+///     .into_response();
+/// assert_eq!(StatusCode::Ok, response.status_code());
+/// assert_eq!(
+///     Some(&b"application/json".to_vec()),
+///     response.get_header(&HeaderName::ContentType)
+/// );
+/// ```
+pub trait IntoResponse {
+    fn into_response(self) -> Response;
+}
+
+/// HTML sent with `Content-Type: text/html; charset=utf-8`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Html<T>(pub T);
+
+macro_rules! impl_into_response_with_from {
+    ($($ty:ty),+) => {
+        $(
+        impl IntoResponse for $ty {
+            fn into_response(self) -> Response {
+                self.into()
+            }
+        }
+        )+
+    };
+}
+
+impl_into_response_with_from!(
+    Response,
+    String,
+    &'static str,
+    Vec<u8>,
+    &'static [u8],
+    Body,
+    StatusCode,
+    Redirect
+);
+
+/// Body serialized as JSON with `Content-Type: application/json`. A value which cannot be
+/// serialized results in `500 Internal Server Error`.
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self.0) {
+            Ok(bytes) => Response::builder()
+                .set_content_type(mime::APPLICATION_JSON)
+                .set_body(bytes)
+                .build(),
+            Err(err) => Error::new(
+                StatusCode::InternalServerError,
+                "failed to serialize response",
+            )
+            .set_source(err)
+            .into_response(),
+        }
+    }
+}
+
+impl<T: Into<Body>> IntoResponse for Html<T> {
+    fn into_response(self) -> Response {
+        Response::builder()
+            .set_content_type(b"text/html; charset=utf-8")
+            .set_body(self.0)
+            .build()
+    }
+}
+
+/// Override the status code of `T`. An `Error` is rendered again with the status code so that the
+/// body agrees with it.
+impl<T: IntoResponse> IntoResponse for (StatusCode, T) {
+    fn into_response(self) -> Response {
+        let (status_code, inner) = self;
+        let mut response = inner.into_response();
+        response.status_code = status_code;
+        if let Some(error) = response.extensions.remove::<Error>() {
+            let error = error.set_status(status_code);
+            error.render(&mut response, None);
+            response.insert_extension(error);
+        }
+        response
+    }
+}
+
+/// Override the status code of `T` and add headers. Headers in `H` replace ones of `T` with the
+/// same name, and a name repeated in `H` is sent with all of the values.
+impl<H, V, T> IntoResponse for (StatusCode, H, T)
+where
+    H: IntoIterator<Item = (HeaderName, V)>,
+    V: Into<HeaderValue>,
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let (status_code, headers, inner) = self;
+        let mut response = (status_code, inner).into_response();
+        let headers = headers
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect::<HeaderMap>();
+        for (name, _) in headers.iter() {
+            response.headers.remove(name);
+        }
+        for (name, value) in headers.iter() {
+            response.append_header(name.clone(), value.clone());
+        }
+        response
+    }
+}
+
+/// `None` results in `404 Not Found`.
+impl<T: IntoResponse> IntoResponse for Option<T> {
+    fn into_response(self) -> Response {
+        match self {
+            Some(inner) => inner.into_response(),
+            None => StatusCode::NotFound.into(),
+        }
+    }
+}
+
+impl<T: IntoResponse, E: IntoResponse> IntoResponse for Result<T, E> {
+    fn into_response(self) -> Response {
+        match self {
+            Ok(inner) => inner.into_response(),
+            Err(err) => err.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_response() {
+        let response = Json(vec![1, 2, 3]).into_response();
+        assert_eq!(StatusCode::Ok, response.status_code());
+        assert_eq!(&Body::from("[1,2,3]"), response.body());
+        assert_eq!(
+            Some(&mime::APPLICATION_JSON.to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+    }
+
+    #[test]
+    fn unserializable_json_response() {
+        let mut map = std::collections::HashMap::new();
+        map.insert(vec![1u8], 1);
+        let response = Json(map).into_response();
+        assert_eq!(StatusCode::InternalServerError, response.status_code());
+        assert_eq!(
+            Some("failed to serialize response"),
+            response.extension::<Error>().and_then(Error::message)
+        );
+        assert_eq!(
+            Some(&b"application/problem+json".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+    }
+
+    #[test]
+    fn html_response() {
+        let response = Html("<h1>qz</h1>").into_response();
+        assert_eq!(&Body::from("<h1>qz</h1>"), response.body());
+        assert_eq!(
+            Some(&b"text/html; charset=utf-8".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+    }
+
+    #[test]
+    fn tuple_response() {
+        let response = (StatusCode::ImaTeapot, "teapot").into_response();
+        assert_eq!(StatusCode::ImaTeapot, response.status_code());
+        assert_eq!(&Body::from("teapot"), response.body());
+
+        let response = (
+            StatusCode::Ok,
+            vec![
+                (HeaderName::ContentType, "text/csv"),
+                (HeaderName::SetCookie, "a=1"),
+                (HeaderName::SetCookie, "b=2"),
+            ],
+            "a,b",
+        )
+            .into_response();
+        assert_eq!(
+            Some(&b"text/csv".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+        assert_eq!(
+            2,
+            response.headers().get_all(&HeaderName::SetCookie).count()
+        );
+    }
+
+    #[test]
+    fn override_status_code_of_error() {
+        let result: crate::Result<&str> = Err(Error::from(StatusCode::BadRequest));
+        let response = (StatusCode::Conflict, result).into_response();
+        assert_eq!(StatusCode::Conflict, response.status_code());
+        assert_eq!(
+            Some(StatusCode::Conflict),
+            response.extension::<Error>().map(Error::status)
+        );
+        let body: serde_json::Value = response.body().into_json().unwrap();
+        assert_eq!(409, body["status"]);
+        assert_eq!("Conflict", body["title"]);
+    }
+
+    #[test]
+    fn option_and_result_response() {
+        assert_eq!(
+            StatusCode::NotFound,
+            None::<String>.into_response().status_code()
+        );
+        assert_eq!(&Body::from("qz"), Some("qz").into_response().body());
+        let result: crate::Result<&str, StatusCode> = Err(StatusCode::Forbidden);
+        assert_eq!(StatusCode::Forbidden, result.into_response().status_code());
+    }
+}
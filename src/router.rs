@@ -1,24 +1,49 @@
 use crate::{
-    handler::Handler, method::Method, request::Request, response::Response, status::StatusCode,
+    handler::Handler,
+    header::{HeaderName, HeaderValue},
+    method::Method,
+    request::Request,
+    response::Response,
+    status::StatusCode,
 };
+use async_trait::async_trait;
 use std::collections::HashMap;
 
 /// Associates URI with `Handler`.
 /// URI paths are represented as trie tree.
 /// This struct is a node of the tree.
+///
+/// A path can contain named parameters(e.g. `/users/:id`) and a wildcard at the end
+/// (e.g. `/files/*` or `/files/*path`). When a URI matches multiple routes, static path is
+/// preferred to parameters, and parameters are preferred to wildcards.
 #[derive(Debug)]
 pub struct Router<State>
 where
     State: Clone + Send + Sync + 'static,
 {
+    // Literal path for nodes in `children` and name of a parameter or a wildcard for
+    // `param_child` and `catch_all_child`.
     path: Vec<u8>,
     handlers: HashMap<Method, Box<dyn Handler<State>>>,
+    // Answers requests whose method is not in `handlers`.
+    allowed_methods: AllowedMethods,
     children: Vec<Router<State>>,
+    // Node of a named parameter such as `:id`, which matches a segment up to next `/`.
+    param_child: Option<Box<Router<State>>>,
+    // Node of a wildcard such as `*` or `*path`, which matches all of the rest of the URI.
+    catch_all_child: Option<Box<Router<State>>>,
+    // Answers requests which match no route. Only the root node has it.
+    fallback: Option<Box<dyn Handler<State>>>,
 }
 
-/// Check if the path has wild card at the end of the path.
-fn includes_wildcard(path: &[u8]) -> bool {
-    path.ends_with(b"/*")
+/// Result of `Router::find()`.
+pub struct RouteMatch<'a, State>
+where
+    State: Clone + Send + Sync + 'static,
+{
+    pub handler: &'a dyn Handler<State>,
+    /// Values of named parameters and wildcard captured from URI.
+    pub params: HashMap<String, String>,
 }
 
 impl<State> Router<State>
@@ -26,45 +51,24 @@ where
     State: Clone + Send + Sync + 'static,
 {
     pub fn new() -> Self {
+        Self::with_path(b"")
+    }
+
+    fn with_path(path: &[u8]) -> Self {
         Self {
-            path: Vec::new(),
+            path: path.to_vec(),
             handlers: HashMap::new(),
+            allowed_methods: AllowedMethods::default(),
             children: Vec::new(),
+            param_child: None,
+            catch_all_child: None,
+            fallback: None,
         }
     }
 
-    fn new_child<F: Handler<State>>(path: &[u8], method: Method, handler: F) -> Self {
-        if includes_wildcard(path) && !path.starts_with(b"*") {
-            let mut child = Self {
-                path: path.to_vec(),
-                handlers: HashMap::new(),
-                children: Vec::new(),
-            };
-            child.split_wildcard(method, handler);
-            child
-        } else {
-            let mut handlers: HashMap<Method, Box<dyn Handler<State> + 'static>> = HashMap::new();
-            handlers.insert(method, Box::new(handler));
-            Self {
-                path: path.to_vec(),
-                handlers,
-                children: Vec::new(),
-            }
-        }
-    }
-
-    fn split_wildcard<F: Handler<State>>(&mut self, method: Method, handler: F) {
-        assert!(includes_wildcard(&self.path));
-        assert!(self.path.len() >= 2);
-        let (_, path) = self.path.split_last().unwrap();
-        self.path = path.to_vec();
-        let mut handlers: HashMap<Method, Box<dyn Handler<State> + 'static>> = HashMap::new();
-        handlers.insert(method, Box::new(handler));
-        self.children.push(Self {
-            path: b"*".to_vec(),
-            handlers,
-            children: Vec::new(),
-        });
+    /// Set a handler for requests which match no route instead of `404 Not Found`.
+    pub fn set_fallback<F: Handler<State>>(&mut self, handler: F) {
+        self.fallback = Some(Box::new(handler));
     }
 
     /// Return how many common character path of `Route` nodes and an arugument have.
@@ -80,114 +84,190 @@ where
         pos
     }
 
+    /// Split this static node into a node of `self.path[..at]` and its child of the rest.
+    fn split_at(&mut self, at: usize) {
+        let mut child = Self::with_path(&self.path[at..]);
+        child.handlers = std::mem::take(&mut self.handlers);
+        child.allowed_methods = std::mem::take(&mut self.allowed_methods);
+        child.children = std::mem::take(&mut self.children);
+        child.param_child = self.param_child.take();
+        child.catch_all_child = self.catch_all_child.take();
+        self.path.truncate(at);
+        self.children = vec![child];
+    }
+
     pub fn add_route<B: AsRef<[u8]>, F: Handler<State>>(
         &mut self,
         new_path: B,
         method: Method,
         handler: F,
     ) {
-        let new_path = new_path.as_ref();
-        // For the first time to insert node to root.
-        if self.path.is_empty() && self.children.is_empty() {
-            self.children
-                .push(Router::new_child(new_path, method, handler));
-            return;
-        }
-        if self.path == new_path {
-            self.handlers.insert(method, Box::new(handler));
-            return;
-        }
+        self.insert(new_path.as_ref(), method, Box::new(handler));
+    }
 
-        let lcp = self.longest_common_prefix(new_path);
-        if self.path.len() > lcp {
-            // e.g. `self.path` is "static" and longest common prefix is "stat".
-            let path = self.path.clone();
-            let common_prefix = &path[..lcp];
-            let path_remaining = &path[lcp..];
-            let new_path_remaining = &new_path[lcp..];
-
-            self.path = common_prefix.to_vec();
-            let deriving_child = Self {
-                path: path_remaining.to_vec(),
-                handlers: std::mem::take(&mut self.handlers),
-                children: std::mem::take(&mut self.children),
-            };
-            if !new_path_remaining.is_empty() {
-                // e.g. "abc" and "ade".
-                self.children = vec![
-                    deriving_child,
-                    Router::new_child(new_path_remaining, method, handler),
-                ];
-            } else {
-                // e.g. "abc" and "a".
-                // If "a" is inserted in the same way as previous `if` block, a handler for the node "a"
-                // is replaced with `None` but the node has a `handler`.
-                self.handlers.insert(method, Box::new(handler));
-                self.children = vec![deriving_child];
+    /// Insert `handler` to the node for `path`, which is relative to this node.
+    fn insert(&mut self, path: &[u8], method: Method, handler: Box<dyn Handler<State>>) {
+        match path.first() {
+            None => {
+                self.handlers.insert(method, handler);
+                self.allowed_methods = AllowedMethods::new(self.handlers.keys());
             }
-        } else {
-            // When longest common prefix of `new_path` is exactly the same as `self.path`.
-            // e.g. `self.path`: "static" and `new_path`: "static/index.html"
-            let new_path_remaining = &new_path[lcp..];
-            for child in &mut self.children {
-                match (*child).path.get(0) {
-                    // Because more than 2 children node do not have same prefix,
-                    // just check first character of key for each child.
-                    Some(first_char) if first_char == new_path_remaining.iter().next().unwrap() => {
-                        child.add_route(new_path_remaining, method, handler);
-                        return;
+            Some(b':') => {
+                let end = path.iter().position(|&b| b == b'/').unwrap_or(path.len());
+                let name = &path[1..end];
+                let child = self
+                    .param_child
+                    .get_or_insert_with(|| Box::new(Self::with_path(name)));
+                assert!(
+                    child.path == name,
+                    "Parameters with different names are registered at the same position: :{} and :{}",
+                    String::from_utf8_lossy(&child.path),
+                    String::from_utf8_lossy(name),
+                );
+                child.insert(&path[end..], method, handler);
+            }
+            Some(b'*') => {
+                let name = &path[1..];
+                assert!(
+                    !name.contains(&b'/'),
+                    "Wildcard must be at the end of a path: {}",
+                    String::from_utf8_lossy(path)
+                );
+                let child = self
+                    .catch_all_child
+                    .get_or_insert_with(|| Box::new(Self::with_path(name)));
+                assert!(
+                    child.path == name,
+                    "Wildcards with different names are registered at the same position: *{} and *{}",
+                    String::from_utf8_lossy(&child.path),
+                    String::from_utf8_lossy(name),
+                );
+                child.insert(b"", method, handler);
+            }
+            Some(&first_char) => {
+                // Because more than 2 children node do not have same prefix,
+                // just check first character of key for each child.
+                if let Some(child) = self
+                    .children
+                    .iter_mut()
+                    .find(|child| child.path.first() == Some(&first_char))
+                {
+                    let lcp = child.longest_common_prefix(path);
+                    if child.path.len() > lcp {
+                        // e.g. `child.path` is "static" and longest common prefix is "stat".
+                        child.split_at(lcp);
                     }
-                    _ => continue,
+                    child.insert(&path[lcp..], method, handler);
+                    return;
                 }
+                // If there is no child in `self.children` that matches new path, just insert it.
+                let end = path
+                    .iter()
+                    .position(|&b| b == b':' || b == b'*')
+                    .unwrap_or(path.len());
+                let mut child = Self::with_path(&path[..end]);
+                child.insert(&path[end..], method, handler);
+                self.children.push(child);
             }
-            // If there is no child in `self.children` that matches new path, just insert it.
-            self.children
-                .push(Router::new_child(new_path_remaining, method, handler));
         }
     }
 
-    // `find` returns `Handler` even there's no method in the route.
-    // The main purpose is CORS handling which needs to process OPTIONS method for preflight.
-    // if `Router` does not return `Handler` in such error, dummy handler to "/*" for OPTIONS
-    // method should be registered to pass the request to middlewares. This is ugly.
-    pub fn find<'a, B: AsRef<[u8]>>(&'a self, key: B, method: Method) -> &'a dyn Handler<State> {
-        let key = key.as_ref();
-        if key.is_empty() {
-            return &not_found;
+    /// Find a node for `key`, which is relative to this node, satisfying `predicate`.
+    /// Captured parameters are pushed to `params`.
+    fn find_node<'a, P>(
+        &'a self,
+        key: &[u8],
+        predicate: &P,
+        params: &mut Vec<(&'a [u8], Vec<u8>)>,
+    ) -> Option<&'a Self>
+    where
+        P: Fn(&Self) -> bool,
+    {
+        if key.is_empty() && predicate(self) {
+            return Some(self);
         }
-        if &self.path[..] > key {
-            // e.g. `self.path` is "hoge" and `key` is "ho".
-            return &not_found;
+
+        if let Some(child) = self
+            .children
+            .iter()
+            .find(|child| key.starts_with(&child.path))
+        {
+            if let Some(found) = child.find_node(&key[child.path.len()..], predicate, params) {
+                return Some(found);
+            }
         }
-        if key == self.path {
-            match self.handlers.get(&method) {
-                Some(handler) => return &**handler,
-                None => {
-                    if self.children.is_empty() {
-                        return &method_not_allowed;
-                    }
-                    // Try further e.g. `self.path` is "hoge", key is "hoge" and this node has
-                    // wildcard child.
+
+        if let Some(child) = &self.param_child {
+            let end = key.iter().position(|&b| b == b'/').unwrap_or(key.len());
+            if end > 0 {
+                params.push((&child.path, key[..end].to_vec()));
+                if let Some(found) = child.find_node(&key[end..], predicate, params) {
+                    return Some(found);
                 }
+                params.pop();
             }
         }
 
-        let lcp = self.longest_common_prefix(key);
-        let key_remaining = &key[lcp..];
-        for child in &self.children {
-            if &child.path == b"*" {
-                match child.handlers.get(&method) {
-                    Some(handler) => return &**handler,
-                    None => return &method_not_allowed,
-                }
+        if let Some(child) = &self.catch_all_child {
+            if predicate(child) {
+                params.push((&child.path, key.to_vec()));
+                return Some(child);
             }
-            if let (Some(c), Some(d)) = (child.path.get(0), key_remaining.iter().next()) {
-                if c == d {
-                    return child.find(key_remaining, method);
-                }
+        }
+        None
+    }
+
+    fn find_route(&self, key: &[u8], method: &Method) -> Option<RouteMatch<'_, State>> {
+        let mut params = Vec::new();
+        let has_method = |node: &Self| node.handlers.contains_key(method);
+        let node = self.find_node(key, &has_method, &mut params)?;
+        let params = params
+            .into_iter()
+            .filter(|(name, _)| !name.is_empty())
+            .map(|(name, value)| {
+                (
+                    String::from_utf8_lossy(name).into_owned(),
+                    String::from_utf8_lossy(&value).into_owned(),
+                )
+            })
+            .collect();
+        Some(RouteMatch {
+            handler: &*node.handlers[method],
+            params,
+        })
+    }
+
+    /// Find a handler for `key` and `method`.
+    /// If there is no route for `key`, the fallback handler or handler returning
+    /// `404 Not Found` is returned and if there
+    /// is a route for `key` but not for `method`, handler returning `405 Method Not Allowed` is
+    /// returned.
+    /// `key` is supposed to be a percent-decoded path, so captured parameters are decoded too.
+    /// `HEAD` falls back to a handler for `GET` unless a handler for `HEAD` is registered.
+    pub fn find<B: AsRef<[u8]>>(&self, key: B, method: &Method) -> RouteMatch<'_, State> {
+        let key = key.as_ref();
+        if let Some(route) = self.find_route(key, method) {
+            return route;
+        }
+        if *method == Method::Head {
+            if let Some(route) = self.find_route(key, &Method::Get) {
+                return route;
             }
         }
-        &not_found
+
+        let has_any_method = |node: &Self| !node.handlers.is_empty();
+        let handler: &dyn Handler<State> =
+            match self.find_node(key, &has_any_method, &mut Vec::new()) {
+                Some(node) => &node.allowed_methods,
+                None => match &self.fallback {
+                    Some(fallback) => fallback.as_ref(),
+                    None => &not_found,
+                },
+            };
+        RouteMatch {
+            handler,
+            params: HashMap::new(),
+        }
     }
 }
 
@@ -198,11 +278,44 @@ where
     Response::new(StatusCode::NotFound)
 }
 
-pub async fn method_not_allowed<State>(_request: Request, _state: State) -> Response
+/// Handler for a route which has no handler for the method of a request.
+/// `OPTIONS` is answered with `204 No Content` and other methods are answered with
+/// `405 Method Not Allowed`, both of which tell methods of the route in `Allow` header.
+#[derive(Debug, Default)]
+struct AllowedMethods {
+    allow: HeaderValue,
+}
+
+impl AllowedMethods {
+    fn new<'a>(methods: impl Iterator<Item = &'a Method>) -> Self {
+        let mut allow = methods.map(Method::as_str).collect::<Vec<_>>();
+        if allow.contains(&"GET") {
+            allow.push("HEAD");
+        }
+        allow.push("OPTIONS");
+        allow.sort_unstable();
+        allow.dedup();
+        Self {
+            allow: allow.join(", ").into_bytes(),
+        }
+    }
+}
+
+#[async_trait]
+impl<State> Handler<State> for AllowedMethods
 where
     State: Clone + Send + Sync + 'static,
 {
-    Response::new(StatusCode::MethodNotAllowed)
+    async fn call(&self, request: Request, _state: State) -> crate::Result<Response> {
+        let status_code = if *request.method() == Method::Options {
+            StatusCode::NoContent
+        } else {
+            StatusCode::MethodNotAllowed
+        };
+        let mut response = Response::new(status_code);
+        response.set_header(HeaderName::Allow, self.allow.clone());
+        Ok(response)
+    }
 }
 
 #[cfg(test)]
@@ -213,21 +326,13 @@ mod tests {
 
     #[test]
     fn lcp() {
-        let node_x = Router::<()> {
-            path: b"abcde".to_vec(),
-            handlers: HashMap::new(),
-            children: Vec::new(),
-        };
+        let node_x = Router::<()>::with_path(b"abcde");
         assert_eq!(node_x.longest_common_prefix(b"abchoge"), 3);
     }
 
     #[test]
     fn lcp_root() {
-        let node_x = Router::<()> {
-            path: b"".to_vec(),
-            handlers: HashMap::new(),
-            children: Vec::new(),
-        };
+        let node_x = Router::<()>::new();
         assert_eq!(node_x.longest_common_prefix(b"abchoge"), 0);
     }
 
@@ -250,7 +355,7 @@ mod tests {
     impl_dummy_handler!(Dummy, "dummy");
 
     async fn test_route(router: &Router<()>, key: impl AsRef<[u8]>, method: Method) {
-        let handler = router.find(key, method);
+        let handler = router.find(key, &method).handler;
         let response = handler.call(Request::default(), ()).await.unwrap();
         assert_eq!(StatusCode::Ok, response.status_code());
     }
@@ -271,11 +376,37 @@ mod tests {
     async fn invalid_method() {
         let mut tree = Router::new();
         tree.add_route("/example", Method::Get, Dummy);
-        let handler = tree.find(b"/example", Method::Post);
+        let handler = tree.find(b"/example", &Method::Post).handler;
         let response = handler.call(Request::default(), ()).await.unwrap();
         assert_eq!(StatusCode::MethodNotAllowed, response.status_code());
     }
 
+    #[tokio::test]
+    async fn allowed_methods() {
+        let mut tree = Router::new();
+        tree.add_route("/example", Method::Get, Dummy);
+        tree.add_route("/example", Method::Delete, Dummy);
+        tree.add_route("/exam", Method::Post, Dummy);
+
+        let request = Request::builder().set_method(Method::Put).build();
+        let handler = tree.find(b"/example", request.method()).handler;
+        let response = handler.call(request, ()).await.unwrap();
+        assert_eq!(StatusCode::MethodNotAllowed, response.status_code());
+        assert_eq!(
+            Some(&b"DELETE, GET, HEAD, OPTIONS".to_vec()),
+            response.get_header(&HeaderName::Allow)
+        );
+
+        let request = Request::builder().set_method(Method::Options).build();
+        let handler = tree.find(b"/exam", request.method()).handler;
+        let response = handler.call(request, ()).await.unwrap();
+        assert_eq!(StatusCode::NoContent, response.status_code());
+        assert_eq!(
+            Some(&b"OPTIONS, POST".to_vec()),
+            response.get_header(&HeaderName::Allow)
+        );
+    }
+
     // Generate random alphanumeric string.
     fn random_bytes() -> Vec<u8> {
         use rand::distributions::Alphanumeric;
@@ -346,7 +477,8 @@ mod tests {
     }
 
     async fn extract_body(tree: &Router<()>, key: &[u8], method: Method) -> Body {
-        tree.find(key, method)
+        tree.find(key, &method)
+            .handler
             .call(Request::default(), ())
             .await
             .unwrap()
@@ -393,4 +525,133 @@ mod tests {
             extract_body(&tree, b"/", Method::Post).await
         );
     }
+
+    #[tokio::test]
+    async fn extension_method() {
+        impl_dummy_handler!(Delete, "delete");
+        impl_dummy_handler!(Purge, "purge");
+
+        let mut tree = Router::new();
+        tree.add_route(b"/cache", Method::Delete, Delete);
+        tree.add_route(b"/cache", Method::Extension("PURGE".to_string()), Purge);
+        assert_eq!(
+            Body::Some(b"delete".to_vec()),
+            extract_body(&tree, b"/cache", Method::Delete).await
+        );
+        assert_eq!(
+            Body::Some(b"purge".to_vec()),
+            extract_body(&tree, b"/cache", Method::Extension("PURGE".to_string())).await
+        );
+        // Methods are case-sensitive.
+        let handler = tree
+            .find(b"/cache", &Method::Extension("purge".to_string()))
+            .handler;
+        let response = handler.call(Request::default(), ()).await.unwrap();
+        assert_eq!(StatusCode::MethodNotAllowed, response.status_code());
+    }
+
+    #[tokio::test]
+    async fn head_falls_back_to_get() {
+        impl_dummy_handler!(Get, "get");
+        impl_dummy_handler!(Head, "head");
+
+        let mut tree = Router::new();
+        tree.add_route(b"/get", Method::Get, Get);
+        tree.add_route(b"/head", Method::Get, Get);
+        tree.add_route(b"/head", Method::Head, Head);
+        tree.add_route(b"/post", Method::Post, Get);
+        assert_eq!(
+            Body::Some(b"get".to_vec()),
+            extract_body(&tree, b"/get", Method::Head).await
+        );
+        assert_eq!(
+            Body::Some(b"head".to_vec()),
+            extract_body(&tree, b"/head", Method::Head).await
+        );
+        let handler = tree.find(b"/post", &Method::Head).handler;
+        let response = handler.call(Request::default(), ()).await.unwrap();
+        assert_eq!(StatusCode::MethodNotAllowed, response.status_code());
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn find_with_params() {
+        let mut tree = Router::new();
+        tree.add_route(b"/users/:id", Method::Get, Dummy);
+        tree.add_route(b"/users/:id/posts/:post_id", Method::Get, Dummy);
+        tree.add_route(b"/files/*path", Method::Get, Dummy);
+
+        let found = tree.find(b"/users/42", &Method::Get);
+        assert_eq!(params(&[("id", "42")]), found.params);
+        let found = tree.find(b"/users/42/posts/hello", &Method::Get);
+        assert_eq!(params(&[("id", "42"), ("post_id", "hello")]), found.params);
+        let found = tree.find(b"/files/static/index.html", &Method::Get);
+        assert_eq!(params(&[("path", "static/index.html")]), found.params);
+
+        test_route(&tree, b"/users/42/posts/hello", Method::Get).await;
+        assert_eq!(
+            Body::None,
+            extract_body(&tree, b"/users/", Method::Get).await
+        );
+        assert_eq!(
+            Body::None,
+            extract_body(&tree, b"/users/42/posts", Method::Get).await
+        );
+    }
+
+    #[tokio::test]
+    async fn static_route_precedes_param() {
+        impl_dummy_handler!(Me, "me");
+        impl_dummy_handler!(User, "user");
+        impl_dummy_handler!(Wildcard, "wildcard");
+
+        let mut tree = Router::new();
+        tree.add_route(b"/users/:id", Method::Get, User);
+        tree.add_route(b"/users/me", Method::Get, Me);
+        tree.add_route(b"/users/*", Method::Get, Wildcard);
+        assert_eq!(
+            Body::Some(b"me".to_vec()),
+            extract_body(&tree, b"/users/me", Method::Get).await
+        );
+        assert_eq!(
+            Body::Some(b"user".to_vec()),
+            extract_body(&tree, b"/users/mine", Method::Get).await
+        );
+        assert_eq!(
+            Body::Some(b"wildcard".to_vec()),
+            extract_body(&tree, b"/users/mine/posts", Method::Get).await
+        );
+    }
+
+    #[tokio::test]
+    async fn backtrack_to_param() {
+        impl_dummy_handler!(Static, "static");
+        impl_dummy_handler!(Param, "param");
+
+        let mut tree = Router::new();
+        tree.add_route(b"/a/b/c", Method::Get, Static);
+        tree.add_route(b"/a/:x/d", Method::Get, Param);
+        assert_eq!(
+            Body::Some(b"param".to_vec()),
+            extract_body(&tree, b"/a/b/d", Method::Get).await
+        );
+        assert_eq!(
+            params(&[("x", "b")]),
+            tree.find(b"/a/b/d", &Method::Get).params
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn conflicting_param_names() {
+        let mut tree = Router::new();
+        tree.add_route(b"/users/:id", Method::Get, Dummy);
+        tree.add_route(b"/users/:name", Method::Post, Dummy);
+    }
 }
@@ -1,15 +1,26 @@
 use crate::{
-    handler::Handler,
+    error_handler::{ErrorHandler, ErrorPage},
+    handler::IntoHandler,
+    header::{typed::Accept, HeaderName, HeaderValue},
     method::Method,
     middleware::{Middleware, MiddlewareChain},
     request::{ParseState, Request, RequestBuffer},
     response::Response,
     router::Router,
     static_files::{StaticDir, StaticFile},
+    status::StatusCode,
+    Error,
 };
-use std::{path::Path, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+/// How long an idle connection is kept open waiting for the next request by default.
+const DEFAULT_KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long it may take to receive a whole request after its first byte arrives by default.
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Builder of `Server`.
 /// The purpose of this struct is to make `Server.router` immutable.
@@ -20,6 +31,11 @@ where
     middlewares: Vec<Arc<dyn Middleware<State>>>,
     router: Router<State>,
     state: State,
+    error_handlers: ErrorHandlers,
+    keep_alive_timeout: Duration,
+    read_timeout: Duration,
+    max_requests_per_connection: Option<usize>,
+    max_body_size: usize,
 }
 
 impl ServerBuilder<()> {
@@ -28,6 +44,12 @@ impl ServerBuilder<()> {
     }
 }
 
+impl Default for ServerBuilder<()> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<State> ServerBuilder<State>
 where
     State: Clone + Send + Sync + 'static,
@@ -37,19 +59,91 @@ where
             middlewares: Vec::new(),
             router: Router::new(),
             state,
+            error_handlers: ErrorHandlers::default(),
+            keep_alive_timeout: DEFAULT_KEEP_ALIVE_TIMEOUT,
+            read_timeout: DEFAULT_READ_TIMEOUT,
+            max_requests_per_connection: None,
+            max_body_size: RequestBuffer::DEFAULT_MAX_BODY_SIZE,
         }
     }
 
+    /// Set how long a persistent connection may stay idle before the server closes it.
+    /// It does not limit how long receiving a request or running a handler takes.
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = timeout;
+        self
+    }
+
+    /// Set how long receiving a whole request may take after its first byte arrives.
+    /// A client which is slower than this is responded with `408 Request Timeout`.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// Set how many requests are served over one connection before the server closes it.
+    /// There is no limit by default.
+    pub fn max_requests_per_connection(mut self, max: usize) -> Self {
+        self.max_requests_per_connection = Some(max);
+        self
+    }
+
+    /// Set the maximum size of a request body in bytes. A request with larger body is responded
+    /// with `413 Payload Too Large`.
+    pub fn max_body_size(mut self, max: usize) -> Self {
+        self.max_body_size = max;
+        self
+    }
+
     pub fn with<M: Middleware<State>>(mut self, middleware: M) -> Self {
         self.middlewares.push(Arc::new(middleware));
         self
     }
 
-    pub fn route<F: Handler<State>>(mut self, path: &str, method: Method, handler: F) -> Self {
-        self.router.add_route(path, method, handler);
+    /// Add a route. `handler` is a `Handler` like `async fn(Request, State)` or an async function
+    /// taking extractors, e.g. `async fn(Path<Params>, Json<Body>)`.
+    pub fn route<H, Args>(mut self, path: &str, method: Method, handler: H) -> Self
+    where
+        H: IntoHandler<State, Args>,
+    {
+        self.router.add_route(path, method, handler.into_handler());
         self
     }
 
+    /// Set a handler for requests which match no route. Requests are answered with
+    /// `404 Not Found` by default.
+    pub fn fallback<H, Args>(mut self, handler: H) -> Self
+    where
+        H: IntoHandler<State, Args>,
+    {
+        self.router.set_fallback(handler.into_handler());
+        self
+    }
+
+    /// Set a hook to rewrite error responses of any status code which has no hook set with
+    /// `error_page()`. See `ErrorHandler` for which responses are rewritten.
+    pub fn error_handler<E: ErrorHandler>(mut self, handler: E) -> Self {
+        self.error_handlers.default = Some(Box::new(handler));
+        self
+    }
+
+    /// Set a hook to rewrite error responses of `status_code`.
+    pub fn error_page<E: ErrorHandler>(mut self, status_code: StatusCode, handler: E) -> Self {
+        self.error_handlers
+            .by_status
+            .insert(status_code, Box::new(handler));
+        self
+    }
+
+    /// Send the file as the body of error responses of `status_code`, e.g. `./static/404.html`.
+    pub fn error_page_file<P>(self, status_code: StatusCode, path: P) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let page = ErrorPage::from_file(path)?;
+        Ok(self.error_page(status_code, page))
+    }
+
     /// Serve files under the directory.
     /// `dir` is path to the directory and `serve_at` is a prefix of URI.
     /// e.g. `self.serve_dir("./static/html", /static)` serves files under `./static/html` and
@@ -58,13 +152,27 @@ where
     where
         P: AsRef<Path>,
     {
+        self.route_dir(serve_at, StaticDir::mount(dir, serve_at))
+    }
+
+    /// Same as `serve_dir()`, but responses have `Cache-Control` header, e.g. `max-age=3600`.
+    pub fn serve_dir_with_cache_control<P>(
+        self,
+        serve_at: &str,
+        dir: P,
+        cache_control: impl Into<HeaderValue>,
+    ) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let dir = StaticDir::mount(dir, serve_at).cache_control(cache_control);
+        self.route_dir(serve_at, dir)
+    }
+
+    fn route_dir(self, serve_at: &str, dir: StaticDir) -> Self {
         let mut serve_at_wildcard = serve_at.trim_end_matches('/').to_string();
         serve_at_wildcard.push_str("/*");
-        self.route(
-            &serve_at_wildcard,
-            Method::Get,
-            StaticDir::mount(dir, serve_at),
-        )
+        self.route(&serve_at_wildcard, Method::Get, dir)
     }
 
     pub fn serve_file<P>(self, serve_at: &str, path: P) -> io::Result<Self>
@@ -72,7 +180,21 @@ where
         P: AsRef<Path>,
     {
         let file = StaticFile::mount(path)?;
-        Ok(self.route(&serve_at, Method::Get, file))
+        Ok(self.route(serve_at, Method::Get, file))
+    }
+
+    /// Same as `serve_file()`, but responses have `Cache-Control` header, e.g. `no-cache`.
+    pub fn serve_file_with_cache_control<P>(
+        self,
+        serve_at: &str,
+        path: P,
+        cache_control: impl Into<HeaderValue>,
+    ) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = StaticFile::mount(path)?.cache_control(cache_control);
+        Ok(self.route(serve_at, Method::Get, file))
     }
 
     pub fn build(self) -> Server<State> {
@@ -80,6 +202,11 @@ where
             middlewares: Arc::new(self.middlewares),
             router: Arc::new(self.router),
             state: self.state,
+            error_handlers: Arc::new(self.error_handlers),
+            keep_alive_timeout: self.keep_alive_timeout,
+            read_timeout: self.read_timeout,
+            max_requests_per_connection: self.max_requests_per_connection,
+            max_body_size: self.max_body_size,
         }
     }
 }
@@ -93,6 +220,11 @@ where
     middlewares: Arc<Vec<Arc<dyn Middleware<State>>>>,
     router: Arc<Router<State>>,
     state: State,
+    error_handlers: Arc<ErrorHandlers>,
+    keep_alive_timeout: Duration,
+    read_timeout: Duration,
+    max_requests_per_connection: Option<usize>,
+    max_body_size: usize,
 }
 
 impl Server<()> {
@@ -114,8 +246,12 @@ where
     pub async fn run(server: Self, port: u16) -> io::Result<()> {
         let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
         println!("Listening on {}", listener.local_addr()?);
+        server.serve(listener).await
+    }
+
+    pub(crate) async fn serve(self, listener: TcpListener) -> io::Result<()> {
         loop {
-            let (mut stream, _) = match listener.accept().await {
+            let (stream, _) = match listener.accept().await {
                 Ok(stream) => stream,
                 Err(err) => {
                     eprintln!("{}", err);
@@ -123,53 +259,604 @@ where
                 }
             };
 
-            let server = server.clone();
+            let server = self.clone();
             tokio::spawn(async move {
-                if let Ok(response) = server.process(&mut stream).await {
-                    if let Err(err) = response.send(&mut stream).await {
-                        eprintln!("{}", err);
-                    }
-                    stream.shutdown().await.unwrap();
-                };
+                server.serve_connection(stream).await;
             });
         }
     }
 
-    async fn process(self, stream: &mut TcpStream) -> Result<Response, ()> {
-        let mut request_buf = RequestBuffer::new();
+    /// Serve requests on a connection until the client or the server decides to close it.
+    /// HTTP/1.1 connections are persistent unless `Connection: close` is specified.
+    /// Pipelined requests are answered one by one in the order they arrive.
+    async fn serve_connection(self, mut stream: TcpStream) {
+        let mut request_buf = RequestBuffer::with_max_body_size(self.max_body_size);
+        let mut served = 0;
+        loop {
+            let (mut response, mut keep_alive, is_head) =
+                match self.process(&mut stream, &mut request_buf).await {
+                    Ok(processed) => processed,
+                    // Timed out while idle, closed by the client or failed to read.
+                    Err(()) => break,
+                };
+            served += 1;
+            if let Some(max) = self.max_requests_per_connection {
+                keep_alive &= served < max;
+            }
+            keep_alive &= !wants_close(response.get_header(&HeaderName::Connection));
+            if !keep_alive {
+                response.set_header(HeaderName::Connection, "close");
+            }
+
+            let sent = if is_head {
+                response.send_without_body(&mut stream).await
+            } else {
+                response.send(&mut stream).await
+            };
+            if let Err(err) = sent {
+                eprintln!("{}", err);
+                break;
+            }
+            if !keep_alive {
+                break;
+            }
+        }
+        let _ = stream.shutdown().await;
+    }
+
+    /// Read a request from the stream and generate a response to it.
+    /// The second element of returned tuple tells whether the connection can be kept alive, and
+    /// the third one tells whether the request is `HEAD`, whose response must not have a body.
+    async fn process(
+        &self,
+        stream: &mut TcpStream,
+        request_buf: &mut RequestBuffer,
+    ) -> Result<(Response, bool, bool), ()> {
         let mut buf = vec![0; Self::INITIAL_BUFFER_SIZE];
+        // Bytes left by a previous request may already contain a whole request.
+        let mut state = request_buf.try_parse(&[]);
+        let mut deadline = None;
         loop {
-            match stream.read(&mut buf).await {
-                Ok(0) => return Err(()),
-                Ok(_) => match request_buf.try_parse(&buf) {
-                    Ok(ParseState::Completed) => break,
-                    Ok(_) => continue,
-                    Err(code) => return Ok(Response::from(code)),
-                },
-                Err(_) => {
-                    return Err(());
+            match state {
+                Ok(ParseState::Completed) => break,
+                Ok(_) => (),
+                // The rest of the stream cannot be trusted after a malformed request.
+                Err(code) => {
+                    let response = self.error_handlers.handle(code.into()).await;
+                    return Ok((response, false, false));
                 }
+            }
+            // Only waiting for the first byte of a request is limited by the keep-alive timeout.
+            // Once it arrives, the rest of the request must arrive by the read deadline.
+            let read = if request_buf.is_idle() {
+                time::timeout(self.keep_alive_timeout, stream.read(&mut buf)).await
+            } else {
+                let deadline =
+                    *deadline.get_or_insert_with(|| time::Instant::now() + self.read_timeout);
+                match time::timeout_at(deadline, stream.read(&mut buf)).await {
+                    Ok(read) => Ok(read),
+                    Err(_) => {
+                        let code = StatusCode::RequestTimeout;
+                        let response = self.error_handlers.handle(code.into()).await;
+                        return Ok((response, false, false));
+                    }
+                }
+            };
+            state = match read {
+                Ok(Ok(0)) | Ok(Err(_)) | Err(_) => return Err(()),
+                Ok(Ok(n)) => request_buf.try_parse(&buf[..n]),
             };
         }
 
         let request = request_buf.complete();
+        let keep_alive = !wants_close(request.get_header(HeaderName::Connection));
+        let is_head = *request.method() == Method::Head;
         let response = self.respond(request).await;
-        Ok(response)
+        Ok((response, keep_alive, is_head))
     }
 
-    pub(crate) async fn respond(self, request: Request) -> Response {
+    pub(crate) async fn respond(&self, mut request: Request) -> Response {
         let Server {
             middlewares,
             router,
             state,
+            error_handlers,
+            ..
         } = self;
 
         println!("{}", request);
-        let handler = router.find(request.uri(), request.method());
+        // Routes are matched against decoded path, and query string and fragment are not a part
+        // of them.
+        let path = match request.uri().decoded_path() {
+            Ok(path) => path,
+            Err(code) => return error_handlers.handle(code.into()).await,
+        };
+        let route = router.find(path.as_str(), request.method());
+        request.params = route.params;
+        let handler = route.handler;
         let chain = MiddlewareChain {
             handler,
-            middlewares: &middlewares,
+            middlewares,
         };
-        chain.run(request, state).await
+        // The request is consumed by the chain, so keep `Accept` to render an error.
+        let accept = request.typed_header::<Accept>();
+        let mut response = chain.run(request, state.clone()).await;
+        if let Some(error) = response.extensions_mut().remove::<Error>() {
+            error.render(&mut response, accept.as_ref());
+            response.insert_extension(error);
+        }
+        error_handlers.handle(response).await
+    }
+}
+
+/// Hooks to rewrite error responses. A hook for the status code precedes the default one.
+#[derive(Default)]
+struct ErrorHandlers {
+    by_status: HashMap<StatusCode, Box<dyn ErrorHandler>>,
+    default: Option<Box<dyn ErrorHandler>>,
+}
+
+impl ErrorHandlers {
+    async fn handle(&self, response: Response) -> Response {
+        let status_code = response.status_code();
+        let is_error = status_code.is_client_error() || status_code.is_server_error();
+        // Keep a body which a handler built on purpose.
+        let is_replaceable = response.body().is_none() || response.extension::<Error>().is_some();
+        if !is_error || !is_replaceable {
+            return response;
+        }
+        match self.by_status.get(&status_code).or(self.default.as_ref()) {
+            Some(handler) => handler.call(response).await,
+            None => response,
+        }
+    }
+}
+
+/// Check if `Connection` header contains `close` option.
+fn wants_close(connection: Option<&HeaderValue>) -> bool {
+    connection.is_some_and(|value| {
+        value
+            .split(|&b| b == b',')
+            .any(|option| option.trim_ascii().eq_ignore_ascii_case(b"close"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpStream;
+
+    async fn spawn_server(server: Server<()>) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(server.serve(listener));
+        TcpStream::connect(addr).await.unwrap()
+    }
+
+    fn hello_server() -> ServerBuilder<()> {
+        Server::builder().route("/", Method::Get, || async { "hello" })
+    }
+
+    // Read one response whose body is "hello".
+    async fn read_response(stream: &mut TcpStream) -> String {
+        read_until(stream, "hello").await
+    }
+
+    // Read responses until they end with `last_body`.
+    async fn read_until(stream: &mut TcpStream, last_body: &str) -> String {
+        let mut response = Vec::new();
+        let mut buf = [0; 1024];
+        while !response.ends_with(last_body.as_bytes()) {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert_ne!(0, n, "connection closed unexpectedly");
+            response.extend_from_slice(&buf[..n]);
+        }
+        String::from_utf8(response).unwrap()
+    }
+
+    async fn is_closed(stream: &mut TcpStream) -> bool {
+        let mut buf = [0; 1];
+        matches!(stream.read(&mut buf).await, Ok(0))
+    }
+
+    #[tokio::test]
+    async fn keep_alive() {
+        let mut stream = spawn_server(hello_server().build()).await;
+        for _ in 0..3 {
+            stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+            let response = read_response(&mut stream).await;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(!response.contains("Connection: close"));
+        }
+    }
+
+    #[tokio::test]
+    async fn head() {
+        let mut stream = spawn_server(hello_server().build()).await;
+        stream
+            .write_all(b"HEAD / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n")
+            .await
+            .unwrap();
+        // If a body is sent to `HEAD`, the response to `GET` is preceded by it.
+        let response = read_response(&mut stream).await;
+        assert_eq!(2, response.matches("Content-Length: 5\r\n").count());
+        assert_eq!(1, response.matches("hello").count());
+    }
+
+    async fn render_error(mut response: Response) -> Response {
+        let body = format!("error {}", response.status_code().code());
+        response.set_body(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let server = hello_server()
+            .fallback(|| async { (StatusCode::NotFound, "custom") })
+            .error_handler(render_error)
+            .build();
+        let request = Request::builder().set_uri("/missing").build();
+        let response = server.respond(request).await;
+        assert_eq!(StatusCode::NotFound, response.status_code());
+        assert_eq!(&crate::body::Body::from("custom"), response.body());
+    }
+
+    #[tokio::test]
+    async fn static_files_with_cache_control() {
+        let server = Server::builder()
+            .serve_dir_with_cache_control("/assets", "./tests/assets", "max-age=3600")
+            .serve_file_with_cache_control("/index.html", "./tests/index.html", "no-cache")
+            .unwrap()
+            .build();
+        let request = Request::builder().set_uri("/assets/index.html").build();
+        let response = server.respond(request).await;
+        assert_eq!(StatusCode::Ok, response.status_code());
+        assert_eq!(
+            Some(&b"max-age=3600".to_vec()),
+            response.get_header(&HeaderName::CacheControl)
+        );
+
+        let request = Request::builder().set_uri("/index.html").build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            Some(&b"no-cache".to_vec()),
+            response.get_header(&HeaderName::CacheControl)
+        );
+    }
+
+    #[tokio::test]
+    async fn error_pages() {
+        let server = hello_server()
+            .route("/fail", Method::Get, || async {
+                crate::Result::<&str>::Err(Error::new(StatusCode::InternalServerError, "down"))
+            })
+            .route("/teapot", Method::Get, || async {
+                (StatusCode::ImaTeapot, "teapot")
+            })
+            .error_handler(render_error)
+            .error_page_file(StatusCode::NotFound, "./tests/assets/index.html")
+            .unwrap()
+            .build();
+
+        let request = Request::builder().set_uri("/missing").build();
+        let response = server.respond(request).await;
+        assert_eq!(StatusCode::NotFound, response.status_code());
+        assert_eq!(&crate::body::Body::from("<p>Hello</p>\n"), response.body());
+        assert_eq!(
+            Some(&crate::mime::TEXT_HTML.to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+
+        let request = Request::builder().set_method(Method::Post).build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("error 405"), response.body());
+        assert!(response.get_header(&HeaderName::Allow).is_some());
+
+        let request = Request::builder().set_uri("/fail").build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("error 500"), response.body());
+
+        let request = Request::builder().set_uri("/teapot").build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("teapot"), response.body());
+    }
+
+    #[tokio::test]
+    async fn error_page_for_malformed_request() {
+        let server = hello_server().error_handler(render_error).build();
+        let mut stream = spawn_server(server).await;
+        stream.write_all(b"GET / HTTP/2.0\r\n\r\n").await.unwrap();
+        let response = read_until(&mut stream, "error 505").await;
+        assert!(response.starts_with("HTTP/1.1 505 "));
+    }
+
+    #[tokio::test]
+    async fn connection_close() {
+        let mut stream = spawn_server(hello_server().build()).await;
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_response(&mut stream).await;
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(is_closed(&mut stream).await);
+    }
+
+    #[tokio::test]
+    async fn max_requests_per_connection() {
+        let server = hello_server().max_requests_per_connection(2).build();
+        let mut stream = spawn_server(server).await;
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        assert!(!read_response(&mut stream)
+            .await
+            .contains("Connection: close"));
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        assert!(read_response(&mut stream)
+            .await
+            .contains("Connection: close"));
+        assert!(is_closed(&mut stream).await);
+    }
+
+    #[tokio::test]
+    async fn keep_alive_timeout() {
+        let server = hello_server()
+            .keep_alive_timeout(Duration::from_millis(50))
+            .build();
+        let mut stream = spawn_server(server).await;
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        read_response(&mut stream).await;
+        assert!(is_closed(&mut stream).await);
+    }
+
+    #[tokio::test]
+    async fn slow_handler_outlives_keep_alive_timeout() {
+        let server = Server::builder()
+            .route("/", Method::Get, || async {
+                time::sleep(Duration::from_millis(100)).await;
+                "hello"
+            })
+            .keep_alive_timeout(Duration::from_millis(20))
+            .build();
+        let mut stream = spawn_server(server).await;
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+        let response = read_response(&mut stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[tokio::test]
+    async fn read_timeout() {
+        let server = hello_server()
+            .read_timeout(Duration::from_millis(50))
+            .build();
+        let mut stream = spawn_server(server).await;
+        stream.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+        let response = read_until(&mut stream, "\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+        assert!(response.contains("Connection: close"));
+        assert!(is_closed(&mut stream).await);
+    }
+
+    #[tokio::test]
+    async fn pipelining() {
+        let server = Server::builder()
+            .route("/first", Method::Get, || async { "first" })
+            .route("/second", Method::Get, || async { "second" })
+            .route(
+                "/echo",
+                Method::Post,
+                |request: Request, _: ()| async move { request.body().clone() },
+            )
+            .build();
+        let mut stream = spawn_server(server).await;
+        stream
+            .write_all(
+                b"GET /second HTTP/1.1\r\n\r\n\
+                POST /echo HTTP/1.1\r\nContent-Length: 4\r\n\r\necho\
+                GET /first HTTP/1.1\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        let responses = read_until(&mut stream, "first").await;
+        let bodies = responses
+            .split("HTTP/1.1 200 OK\r\n")
+            .filter(|response| !response.is_empty())
+            .map(|response| response.split("\r\n\r\n").nth(1).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["second", "echo", "first"], bodies);
+    }
+
+    #[tokio::test]
+    async fn pipelining_with_close() {
+        let mut stream = spawn_server(hello_server().build()).await;
+        stream
+            .write_all(
+                b"GET / HTTP/1.1\r\n\r\n\
+                GET / HTTP/1.1\r\nConnection: close\r\n\r\n\
+                GET / HTTP/1.1\r\n\r\n",
+            )
+            .await
+            .unwrap();
+        // The server must close the connection after the second response.
+        let mut responses = String::new();
+        stream.read_to_string(&mut responses).await.unwrap();
+        assert_eq!(2, responses.matches("HTTP/1.1 200 OK").count());
+    }
+
+    fn echo_server() -> ServerBuilder<()> {
+        Server::builder().route(
+            "/echo",
+            Method::Post,
+            |request: Request, _: ()| async move { request.body().clone() },
+        )
+    }
+
+    #[tokio::test]
+    async fn body_across_reads() {
+        let mut stream = spawn_server(echo_server().build()).await;
+        let body = "a".repeat(10000) + "hello";
+        let request = format!(
+            "POST /echo HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        for chunk in body.as_bytes().chunks(3000) {
+            stream.write_all(chunk).await.unwrap();
+            time::sleep(Duration::from_millis(10)).await;
+        }
+        let response = read_response(&mut stream).await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with(&body));
+    }
+
+    #[tokio::test]
+    async fn payload_too_large() {
+        let mut stream = spawn_server(echo_server().max_body_size(4).build()).await;
+        stream
+            .write_all(b"POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+    }
+
+    #[tokio::test]
+    async fn path_params() {
+        let server = Server::builder()
+            .route(
+                "/users/:id",
+                Method::Get,
+                |request: Request, _: ()| async move { request.param("id").unwrap().to_string() },
+            )
+            .build();
+        let request = Request::builder().set_uri("/users/42").build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("42"), response.body());
+
+        let request = Request::builder().set_uri("/users/./J%C3%BCrgen").build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("Jürgen"), response.body());
+
+        let request = Request::builder().set_uri("/users/a%2Fb").build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            crate::status::StatusCode::BadRequest,
+            response.status_code()
+        );
+    }
+
+    #[tokio::test]
+    async fn extractor_handler() {
+        use crate::extract::{Json, Path, State};
+
+        #[derive(serde::Deserialize)]
+        struct Params {
+            id: u32,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Comment {
+            text: String,
+        }
+
+        async fn post_comment(
+            Path(params): Path<Params>,
+            State(prefix): State<&'static str>,
+            Json(comment): Json<Comment>,
+        ) -> String {
+            format!("{}{}: {}", prefix, params.id, comment.text)
+        }
+
+        let server = Server::builder_with_state("#")
+            .route("/posts/:id/comments", Method::Post, post_comment)
+            .build();
+        let request = Request::builder()
+            .set_method(Method::Post)
+            .set_uri("/posts/42/comments")
+            .set_header(HeaderName::ContentType, "application/json")
+            .set_body(r#"{"text": "nice"}"#)
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("#42: nice"), response.body());
+
+        let request = Request::builder()
+            .set_method(Method::Post)
+            .set_uri("/posts/abc/comments")
+            .set_header(HeaderName::ContentType, "application/json")
+            .set_body(r#"{"text": "nice"}"#)
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            crate::status::StatusCode::BadRequest,
+            response.status_code()
+        );
+
+        let request = Request::builder()
+            .set_method(Method::Post)
+            .set_uri("/posts/42/comments")
+            .set_body("text=nice")
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            crate::status::StatusCode::UnsupportedMediaType,
+            response.status_code()
+        );
+    }
+
+    #[tokio::test]
+    async fn error_response() {
+        let server = Server::builder()
+            .route(
+                "/json",
+                Method::Post,
+                |request: Request, _: ()| async move {
+                    request.body_json::<u32>().map(|n| n.to_string())
+                },
+            )
+            .build();
+        let request = Request::builder()
+            .set_method(Method::Post)
+            .set_uri("/json")
+            .set_body("\"a\"")
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            crate::status::StatusCode::BadRequest,
+            response.status_code()
+        );
+        assert_eq!(
+            Some(&b"application/problem+json".to_vec()),
+            response.get_header(&HeaderName::ContentType)
+        );
+
+        let request = Request::builder()
+            .set_method(Method::Post)
+            .set_uri("/json")
+            .set_header(HeaderName::Accept, "text/plain")
+            .build();
+        let response = server.respond(request).await;
+        assert_eq!(
+            &crate::body::Body::from("Bad Request: request body is empty"),
+            response.body()
+        );
+    }
+
+    #[tokio::test]
+    async fn route_without_query() {
+        #[derive(serde::Deserialize)]
+        struct Search {
+            q: String,
+        }
+
+        let server = Server::builder()
+            .route(
+                "/search",
+                Method::Get,
+                |request: Request, _: ()| async move { request.query::<Search>().unwrap().q },
+            )
+            .build();
+        let request = Request::builder().set_uri("/search?q=qz").build();
+        let response = server.respond(request).await;
+        assert_eq!(&crate::body::Body::from("qz"), response.body());
     }
 }
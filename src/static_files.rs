@@ -1,38 +1,135 @@
+mod range;
 mod static_dir;
 mod static_file;
 
-use crate::{status::StatusCode, Uri};
+use crate::{
+    body::Body,
+    date::HttpDate,
+    header::{
+        typed::{ETag, EntityTag, IfModifiedSince, IfNoneMatch, IfRange, LastModified, Range},
+        HeaderName, HeaderValue,
+    },
+    mime,
+    request::Request,
+    response::Response,
+    status::StatusCode,
+    UriPath,
+};
 pub use static_dir::StaticDir;
 pub use static_file::StaticFile;
 use std::{
-    ffi::OsStr,
-    path::{Path, PathBuf},
+    fs::Metadata,
+    path::{Component, Path, PathBuf},
+    time::UNIX_EPOCH,
 };
+use tokio::fs::File;
+
+/// Create a response whose body is streamed from the file, so that a large file is not loaded
+/// onto memory at once.
+/// Responses carry `ETag` and `Last-Modified` so that clients can validate their caches with
+/// `If-None-Match` and `If-Modified-Since`.
+/// If the request has `Range` header, only requested ranges of the file are served.
+async fn serve_file(
+    path: &Path,
+    request: &Request,
+    cache_control: Option<&HeaderValue>,
+) -> crate::Result<Response> {
+    let file = File::open(path).await?;
+    let metadata = file.metadata().await?;
+    if !metadata.is_file() {
+        return Err(StatusCode::NotFound.into());
+    }
+    let file_len = metadata.len();
+    let mime_type = mime::filename_to_mime(path);
+    let modified = metadata.modified().ok().map(HttpDate::from);
+    let etag = etag(&metadata);
+
+    let mut response = if is_not_modified(request, etag.as_ref(), modified) {
+        Response::new(StatusCode::NotModified)
+    } else {
+        let if_range_matches = request
+            .typed_header::<IfRange>()
+            .is_none_or(|if_range| if_range.matches(etag.as_ref(), modified));
+        let ranges = request
+            .typed_header::<Range>()
+            .filter(|_| if_range_matches)
+            .map(|range| range::resolve_ranges(&range, file_len))
+            .filter(|ranges| ranges.len() <= range::MAX_RANGES);
+        match ranges {
+            Some(ranges) => range::respond_ranges(path, file, file_len, mime_type, &ranges).await?,
+            None => {
+                let mut response = Response::from(Body::from_reader_with_len(file, file_len));
+                response.set_content_type(mime_type);
+                response
+            }
+        }
+    };
+    response.set_header(HeaderName::AcceptRanges, "bytes");
+    if let Some(etag) = etag {
+        response.set_typed_header(&ETag(etag));
+    }
+    if let Some(modified) = modified {
+        response.set_typed_header(&LastModified(modified));
+    }
+    if let Some(cache_control) = cache_control {
+        response.set_header(HeaderName::CacheControl, cache_control.clone());
+    }
+    Ok(response)
+}
+
+/// Make a strong entity tag from modification time and size of the file.
+fn etag(metadata: &Metadata) -> Option<EntityTag> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(EntityTag::strong(format!(
+        "{:x}.{:x}-{:x}",
+        modified.as_secs(),
+        modified.subsec_nanos(),
+        metadata.len()
+    )))
+}
+
+/// Check if the cache of a client is still valid.
+/// `If-Modified-Since` is evaluated only when there is no `If-None-Match`.
+fn is_not_modified(
+    request: &Request,
+    etag: Option<&EntityTag>,
+    modified: Option<HttpDate>,
+) -> bool {
+    if request.get_header(HeaderName::IfNoneMatch).is_some() {
+        return match (request.typed_header::<IfNoneMatch>(), etag) {
+            (Some(if_none_match), Some(etag)) => if_none_match.matches(etag),
+            _ => false,
+        };
+    }
+    match (request.typed_header::<IfModifiedSince>(), modified) {
+        (Some(IfModifiedSince(since)), Some(modified)) => modified <= since,
+        _ => false,
+    }
+}
 
-fn find_file(path: &Uri, mount_dir: &Path, serve_at: &Path) -> crate::Result<PathBuf> {
-    let path = std::str::from_utf8(&path.0).unwrap();
-    let path = Path::new(path);
+fn find_file(
+    path: &UriPath,
+    mount_dir: &Path,
+    serve_at: &Path,
+) -> crate::Result<PathBuf, StatusCode> {
+    let path = Path::new(path.as_str());
     let path = match path.strip_prefix(serve_at) {
         Ok(path) => path,
         Err(_) => return Err(StatusCode::NotFound),
     };
 
     let mut file_to_find = mount_dir.to_path_buf();
-    for p in path {
-        if p == OsStr::new(".") {
-            continue;
-        } else if p == OsStr::new("..") {
-            if !file_to_find.pop() {
-                // Forbid to access file which is out of mount point to prevent directory
-                // traversal attack.
-                return Err(StatusCode::Forbidden);
-            }
-        } else {
-            file_to_find.push(p);
+    for component in path.components() {
+        match component {
+            Component::Normal(segment) => file_to_find.push(segment),
+            // `UriPath` has neither `.` nor `..` segments, but a segment can still be meaningful
+            // to the platform, e.g. `a\..\b` or `C:` on Windows. Forbid them to prevent directory
+            // traversal attack.
+            _ => return Err(StatusCode::Forbidden),
         }
     }
 
-    if !file_to_find.starts_with(&mount_dir) {
+    if !file_to_find.starts_with(mount_dir) {
         return Err(StatusCode::NotFound);
     }
     if !file_to_find.exists() {
@@ -44,12 +141,16 @@ fn find_file(path: &Uri, mount_dir: &Path, serve_at: &Path) -> crate::Result<Pat
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Uri;
+    use std::convert::TryFrom;
     use tokio::{
         fs::{self, File},
         io,
     };
 
+    fn path(path: &str) -> UriPath {
+        UriPath::try_from(path.as_bytes()).unwrap()
+    }
+
     // Creates ./static_dir_test/static/index.html for tests.
     // First this function uses `tempfile` crate, but temporary directory which is created with the
     // crate is deleted when it goes out of scope.
@@ -66,7 +167,7 @@ mod tests {
     async fn find_static_file() -> io::Result<()> {
         let (static_dir, serve_at) = setup_dir().await?;
         find_file(
-            &Uri::new(b"/static/index.html"),
+            &path("/static/index.html"),
             static_dir.as_path(),
             serve_at.as_path(),
         )
@@ -78,7 +179,7 @@ mod tests {
     async fn find_missing_file() -> io::Result<()> {
         let (static_dir, serve_at) = setup_dir().await?;
         assert!(find_file(
-            &Uri::new(b"/static/index.css"),
+            &path("/static/index.css"),
             static_dir.as_path(),
             serve_at.as_path()
         )
@@ -97,11 +198,24 @@ mod tests {
          * `-/secret.txt
          */
         assert!(find_file(
-            &Uri::new(b"/static/../../secret.txt"),
+            &path("/static/../../secret.txt"),
             static_dir.as_path(),
             serve_at.as_path()
         )
         .is_err());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn find_file_with_encoded_name() -> io::Result<()> {
+        let (static_dir, serve_at) = setup_dir().await?;
+        File::create(static_dir.join("my file.txt")).await?;
+        find_file(
+            &path("/static/my%20file.txt"),
+            static_dir.as_path(),
+            serve_at.as_path(),
+        )
+        .unwrap();
+        Ok(())
+    }
 }
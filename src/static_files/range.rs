@@ -0,0 +1,283 @@
+use crate::{
+    body::Body,
+    header::{typed::Range, HeaderName},
+    response::Response,
+    status::StatusCode,
+};
+use std::{
+    cmp,
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    io::{Cursor, SeekFrom},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::{
+    fs::File,
+    io::{self, AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, ReadBuf},
+};
+
+/// Maximum number of ranges served for one request. A request with more ranges after
+/// coalescing them is responded with the whole file, so that a client cannot amplify a response
+/// with many ranges.
+pub(crate) const MAX_RANGES: usize = 16;
+
+/// Range of bytes in a file. Both ends are inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ByteRange {
+    start: u64,
+    end: u64,
+}
+
+impl ByteRange {
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    fn content_range(&self, file_len: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, file_len)
+    }
+}
+
+/// Resolve ranges in `Range` header against length of a file.
+/// Unsatisfiable ranges are removed from returned ranges, and overlapping or adjacent ones are
+/// coalesced into one range. Returned ranges are sorted by their start.
+pub(crate) fn resolve_ranges(range: &Range, file_len: u64) -> Vec<ByteRange> {
+    let mut ranges = range
+        .satisfiable_ranges(file_len)
+        .into_iter()
+        .map(|(start, end)| ByteRange { start, end })
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+    let mut coalesced: Vec<ByteRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match coalesced.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(1) => {
+                last.end = cmp::max(last.end, range.end);
+            }
+            _ => coalesced.push(range),
+        }
+    }
+    coalesced
+}
+
+/// Make a boundary of multipart body which is unlikely to appear in the file.
+fn boundary(path: &Path, file_len: u64) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    file_len.hash(&mut hasher);
+    SystemTime::now().hash(&mut hasher);
+    format!("qz-byteranges-{:016x}", hasher.finish())
+}
+
+enum Segment {
+    Bytes(Cursor<Vec<u8>>),
+    /// Range of the file and whether the file has been seeked to its start.
+    Range(ByteRange, bool),
+}
+
+/// Reader of `multipart/byteranges` body. Every part is read from one file by seeking to the
+/// start of its range.
+struct ByteRangesReader {
+    file: File,
+    segments: VecDeque<Segment>,
+    seeking: bool,
+}
+
+impl AsyncRead for ByteRangesReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        loop {
+            match this.segments.front_mut() {
+                None => return Poll::Ready(Ok(())),
+                Some(Segment::Bytes(bytes)) => {
+                    let filled = buf.filled().len();
+                    if let Poll::Ready(Err(err)) = Pin::new(&mut *bytes).poll_read(cx, buf) {
+                        return Poll::Ready(Err(err));
+                    }
+                    if buf.filled().len() > filled {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Some(Segment::Range(range, seeked)) if range.start <= range.end => {
+                    if !*seeked {
+                        if !this.seeking {
+                            Pin::new(&mut this.file).start_seek(SeekFrom::Start(range.start))?;
+                            this.seeking = true;
+                        }
+                        match Pin::new(&mut this.file).poll_complete(cx) {
+                            Poll::Ready(result) => result?,
+                            Poll::Pending => return Poll::Pending,
+                        };
+                        this.seeking = false;
+                        *seeked = true;
+                    }
+                    let limit = cmp::min(range.len(), buf.remaining() as u64) as usize;
+                    let mut part = ReadBuf::new(buf.initialize_unfilled_to(limit));
+                    match Pin::new(&mut this.file).poll_read(cx, &mut part) {
+                        Poll::Ready(result) => result?,
+                        Poll::Pending => return Poll::Pending,
+                    }
+                    let n = part.filled().len();
+                    if n == 0 {
+                        return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                    buf.advance(n);
+                    range.start += n as u64;
+                    return Poll::Ready(Ok(()));
+                }
+                // Whole of the range has been read.
+                Some(Segment::Range(..)) => (),
+            }
+            this.segments.pop_front();
+        }
+    }
+}
+
+/// Create a response for ranges of a file.
+/// Multiple ranges are sent as `multipart/byteranges`.
+pub(crate) async fn respond_ranges(
+    path: &Path,
+    mut file: File,
+    file_len: u64,
+    mime_type: &[u8],
+    ranges: &[ByteRange],
+) -> crate::Result<Response> {
+    match ranges {
+        [] => {
+            let mut response = Response::new(StatusCode::RangeNotSatisfiable);
+            response.set_header(HeaderName::ContentRange, format!("bytes */{}", file_len));
+            Ok(response)
+        }
+        [range] => {
+            file.seek(SeekFrom::Start(range.start)).await?;
+            let body = Body::from_reader_with_len(file.take(range.len()), range.len());
+            let mut response = Response::from(body);
+            response.status_code = StatusCode::PartialContent;
+            response.set_content_type(mime_type);
+            response.set_header(HeaderName::ContentRange, range.content_range(file_len));
+            Ok(response)
+        }
+        ranges => {
+            let boundary = boundary(path, file_len);
+            let mut segments = VecDeque::with_capacity(ranges.len() * 2 + 1);
+            let mut body_len = 0;
+            for (i, range) in ranges.iter().enumerate() {
+                let mut part_header = if i == 0 { Vec::new() } else { b"\r\n".to_vec() };
+                part_header.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+                part_header.extend_from_slice(b"Content-Type: ");
+                part_header.extend_from_slice(mime_type);
+                part_header.extend_from_slice(
+                    format!(
+                        "\r\nContent-Range: {}\r\n\r\n",
+                        range.content_range(file_len)
+                    )
+                    .as_bytes(),
+                );
+                body_len += part_header.len() as u64 + range.len();
+                segments.push_back(Segment::Bytes(Cursor::new(part_header)));
+                segments.push_back(Segment::Range(*range, false));
+            }
+            let closing = format!("\r\n--{}--\r\n", boundary).into_bytes();
+            body_len += closing.len() as u64;
+            segments.push_back(Segment::Bytes(Cursor::new(closing)));
+            let body = ByteRangesReader {
+                file,
+                segments,
+                seeking: false,
+            };
+
+            let mut response = Response::from(Body::from_reader_with_len(body, body_len));
+            response.status_code = StatusCode::PartialContent;
+            response.set_content_type(
+                format!("multipart/byteranges; boundary={}", boundary).as_bytes(),
+            );
+            Ok(response)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::header::typed::TypedHeader;
+
+    fn range(start: u64, end: u64) -> ByteRange {
+        ByteRange { start, end }
+    }
+
+    // `None` means the header is invalid and should be ignored.
+    fn parse_range(value: &[u8], file_len: u64) -> Option<Vec<ByteRange>> {
+        Range::decode(value)
+            .ok()
+            .map(|range| resolve_ranges(&range, file_len))
+    }
+
+    #[test]
+    fn parse_single_range() {
+        assert_eq!(Some(vec![range(0, 499)]), parse_range(b"bytes=0-499", 1000));
+        assert_eq!(
+            Some(vec![range(500, 999)]),
+            parse_range(b"bytes=500-", 1000)
+        );
+        assert_eq!(
+            Some(vec![range(900, 999)]),
+            parse_range(b"bytes=-100", 1000)
+        );
+        assert_eq!(Some(vec![range(0, 999)]), parse_range(b"bytes=-2000", 1000));
+        assert_eq!(
+            Some(vec![range(500, 999)]),
+            parse_range(b"bytes=500-2000", 1000)
+        );
+    }
+
+    #[test]
+    fn parse_multiple_ranges() {
+        assert_eq!(
+            Some(vec![range(0, 0), range(10, 19), range(999, 999)]),
+            parse_range(b"bytes=0-0, 10-19,-1", 1000)
+        );
+    }
+
+    #[test]
+    fn coalesce_ranges() {
+        assert_eq!(
+            Some(vec![range(0, 999)]),
+            parse_range(b"bytes=0-,0-,0-", 1000)
+        );
+        assert_eq!(
+            Some(vec![range(0, 19), range(30, 999)]),
+            parse_range(b"bytes=10-19,0-9,35-,30-39", 1000)
+        );
+    }
+
+    #[test]
+    fn parse_unsatisfiable_range() {
+        assert_eq!(Some(vec![]), parse_range(b"bytes=1000-", 1000));
+        assert_eq!(Some(vec![]), parse_range(b"bytes=-0", 1000));
+        assert_eq!(
+            Some(vec![range(0, 9)]),
+            parse_range(b"bytes=0-9,2000-3000", 1000)
+        );
+    }
+
+    #[test]
+    fn parse_invalid_range() {
+        assert_eq!(None, parse_range(b"items=0-9", 1000));
+        assert_eq!(None, parse_range(b"bytes=9-0", 1000));
+        assert_eq!(None, parse_range(b"bytes=a-b", 1000));
+        assert_eq!(None, parse_range(b"bytes=0", 1000));
+        assert_eq!(None, parse_range(b"bytes=+0-+9", 1000));
+        assert_eq!(None, parse_range(b"bytes=-+9", 1000));
+    }
+}
@@ -1,15 +1,19 @@
 use std::path::{Path, PathBuf};
 
 use crate::{
-    handler::Handler, mime, request::Request, response::Response, static_files::find_file,
+    handler::Handler,
+    header::HeaderValue,
+    request::Request,
+    response::Response,
+    static_files::{find_file, serve_file},
 };
 use async_trait::async_trait;
-use tokio::{fs::File, io::AsyncReadExt};
 
 #[derive(Debug)]
 pub struct StaticDir {
     mount_dir: PathBuf,
     serve_at: PathBuf,
+    cache_control: Option<HeaderValue>,
 }
 
 impl StaticDir {
@@ -21,8 +25,15 @@ impl StaticDir {
         Self {
             mount_dir: mount_dir.as_ref().to_path_buf(),
             serve_at: serve_at.as_ref().to_path_buf(),
+            cache_control: None,
         }
     }
+
+    /// Set `Cache-Control` header of responses, e.g. `max-age=3600`.
+    pub fn cache_control(mut self, cache_control: impl Into<HeaderValue>) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
 }
 
 #[async_trait]
@@ -32,17 +43,10 @@ where
 {
     async fn call(&self, request: Request, _state: State) -> crate::Result<Response> {
         let found_file = find_file(
-            request.uri(),
+            &request.uri().decoded_path()?,
             self.mount_dir.as_path(),
             self.serve_at.as_path(),
         )?;
-
-        let mime_type = mime::filename_to_mime(&found_file);
-        let mut file_to_serve = File::open(found_file).await?;
-        let mut buffer = Vec::new();
-        file_to_serve.read_to_end(&mut buffer).await?;
-        let mut response = Response::from(buffer);
-        response.set_content_type(mime_type);
-        Ok(response)
+        serve_file(&found_file, &request, self.cache_control.as_ref()).await
     }
 }
@@ -3,18 +3,30 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::{handler::Handler, mime, request::Request, response::Response};
+use crate::{
+    handler::Handler, header::HeaderValue, request::Request, response::Response,
+    static_files::serve_file,
+};
 use async_trait::async_trait;
-use tokio::{fs::File, io::AsyncReadExt};
 
 pub struct StaticFile {
     path: PathBuf,
+    cache_control: Option<HeaderValue>,
 }
 
 impl StaticFile {
     pub fn mount<P: AsRef<Path>>(path: P) -> io::Result<Self> {
         let path = path.as_ref().canonicalize()?;
-        Ok(Self { path })
+        Ok(Self {
+            path,
+            cache_control: None,
+        })
+    }
+
+    /// Set `Cache-Control` header of responses, e.g. `max-age=3600`.
+    pub fn cache_control(mut self, cache_control: impl Into<HeaderValue>) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
     }
 }
 
@@ -23,16 +35,7 @@ impl<State> Handler<State> for StaticFile
 where
     State: Clone + Send + Sync + 'static,
 {
-    async fn call(&self, _request: Request, _state: State) -> crate::Result<Response> {
-        let filename = self.path.clone();
-        let mime_type = mime::filename_to_mime(&filename);
-        // This should return internal server error.
-        let mut file_to_serve = File::open(filename).await?;
-
-        let mut buffer = Vec::new();
-        file_to_serve.read_to_end(&mut buffer).await?;
-        let mut response = Response::from(buffer);
-        response.set_content_type(mime_type);
-        Ok(response)
+    async fn call(&self, request: Request, _state: State) -> crate::Result<Response> {
+        serve_file(&self.path, &request, self.cache_control.as_ref()).await
     }
 }
@@ -1,13 +1,23 @@
 use crate::response::Response;
-use std::io;
+use std::{
+    convert::TryFrom,
+    error, fmt,
+    hash::{Hash, Hasher},
+    io,
+};
 
 macro_rules! define_status_codes {
     ($(($num:expr, $entry:ident, $phrase:expr),)+) => {
-        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        /// HTTP status code. Codes in the IANA registry have their own variants and others are
+        /// represented as `Custom` with a reason phrase, which is created by
+        /// `StatusCode::custom()` or `StatusCode::try_from()`.
+        /// Status codes are compared only by their numbers.
+        #[derive(Clone, Copy, Debug)]
         pub enum StatusCode {
             $(
             $entry,
             )+
+            Custom(CustomStatusCode),
         }
 
         impl StatusCode {
@@ -17,6 +27,7 @@ macro_rules! define_status_codes {
                     $(
                     StatusCode::$entry => $num,
                     )+
+                    StatusCode::Custom(custom) => custom.code,
                 }
             }
 
@@ -26,28 +37,151 @@ macro_rules! define_status_codes {
                     $(
                     StatusCode::$entry => $phrase.as_bytes(),
                     )+
+                    StatusCode::Custom(custom) => custom.phrase.as_bytes(),
+                }
+            }
+        }
+
+        impl StatusCode {
+            /// Create a status code with a reason phrase. A registered code is converted to its
+            /// variant ignoring `phrase`. A code which is not 3 digits and a phrase with control
+            /// characters, which would break the status line, are rejected.
+            pub fn custom(code: u16, phrase: &'static str) -> Result<Self, InvalidStatusCode> {
+                let is_valid_phrase = phrase
+                    .bytes()
+                    .all(|b| b == b'\t' || (b' '..=b'~').contains(&b) || b >= 0x80);
+                if !is_valid_phrase {
+                    return Err(InvalidStatusCode(code));
+                }
+                match code {
+                    $(
+                    $num => Ok(StatusCode::$entry),
+                    )+
+                    100..=999 => Ok(StatusCode::Custom(CustomStatusCode { code, phrase })),
+                    _ => Err(InvalidStatusCode(code)),
                 }
             }
         }
     }
 }
 
+// https://www.iana.org/assignments/http-status-codes/http-status-codes.xhtml
 define_status_codes!(
+    (100, Continue, "Continue"),
+    (101, SwitchingProtocols, "Switching Protocols"),
+    (102, Processing, "Processing"),
+    (103, EarlyHints, "Early Hints"),
     (200, Ok, "OK"),
+    (201, Created, "Created"),
+    (202, Accepted, "Accepted"),
+    (
+        203,
+        NonAuthoritativeInformation,
+        "Non-Authoritative Information"
+    ),
+    (204, NoContent, "No Content"),
+    (205, ResetContent, "Reset Content"),
+    (206, PartialContent, "Partial Content"),
+    (207, MultiStatus, "Multi-Status"),
+    (208, AlreadyReported, "Already Reported"),
+    (226, ImUsed, "IM Used"),
+    (300, MultipleChoices, "Multiple Choices"),
     (301, MovedPermanently, "Moved Permanently"),
     (302, Found, "Found"),
     (303, SeeOther, "See Other"),
+    (304, NotModified, "Not Modified"),
+    (305, UseProxy, "Use Proxy"),
+    (307, TemporaryRedirect, "Temporary Redirect"),
+    (308, PermanentRedirect, "Permanent Redirect"),
     (400, BadRequest, "Bad Request"),
     (401, Unauthorized, "Unauthorized"),
+    (402, PaymentRequired, "Payment Required"),
     (403, Forbidden, "Forbidden"),
     (404, NotFound, "Not Found"),
     (405, MethodNotAllowed, "Method Not Allowed"),
+    (406, NotAcceptable, "Not Acceptable"),
+    (
+        407,
+        ProxyAuthenticationRequired,
+        "Proxy Authentication Required"
+    ),
+    (408, RequestTimeout, "Request Timeout"),
+    (409, Conflict, "Conflict"),
+    (410, Gone, "Gone"),
     (411, LengthRequired, "Length Required"),
+    (412, PreconditionFailed, "Precondition Failed"),
+    (413, PayloadTooLarge, "Payload Too Large"),
+    (414, UriTooLong, "URI Too Long"),
+    (415, UnsupportedMediaType, "Unsupported Media Type"),
+    (416, RangeNotSatisfiable, "Range Not Satisfiable"),
+    (417, ExpectationFailed, "Expectation Failed"),
     (418, ImaTeapot, "I'm a teapot"),
+    (421, MisdirectedRequest, "Misdirected Request"),
+    (422, UnprocessableContent, "Unprocessable Content"),
+    (423, Locked, "Locked"),
+    (424, FailedDependency, "Failed Dependency"),
+    (425, TooEarly, "Too Early"),
+    (426, UpgradeRequired, "Upgrade Required"),
+    (428, PreconditionRequired, "Precondition Required"),
+    (429, TooManyRequests, "Too Many Requests"),
+    (
+        431,
+        RequestHeaderFieldsTooLarge,
+        "Request Header Fields Too Large"
+    ),
+    (
+        451,
+        UnavailableForLegalReasons,
+        "Unavailable For Legal Reasons"
+    ),
     (500, InternalServerError, "Internal Server Error"),
+    (501, NotImplemented, "Not Implemented"),
+    (502, BadGateway, "Bad Gateway"),
+    (503, ServiceUnavailable, "Service Unavailable"),
+    (504, GatewayTimeout, "Gateway Timeout"),
     (505, HttpVersionNotSupported, "HTTP Version not Supported"),
+    (506, VariantAlsoNegotiates, "Variant Also Negotiates"),
+    (507, InsufficientStorage, "Insufficient Storage"),
+    (508, LoopDetected, "Loop Detected"),
+    (510, NotExtended, "Not Extended"),
+    (
+        511,
+        NetworkAuthenticationRequired,
+        "Network Authentication Required"
+    ),
 );
 
+/// Status code which is not in the IANA registry. It can be created only by `StatusCode::custom()`
+/// or `StatusCode::try_from()` so that it is always valid.
+#[derive(Clone, Copy, Debug)]
+pub struct CustomStatusCode {
+    code: u16,
+    phrase: &'static str,
+}
+
+/// Registered codes are converted to their variants and other 3-digit codes are converted to
+/// `Custom` with an empty reason phrase.
+impl TryFrom<u16> for StatusCode {
+    type Error = InvalidStatusCode;
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        StatusCode::custom(code, "")
+    }
+}
+
+impl PartialEq for StatusCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code()
+    }
+}
+
+impl Eq for StatusCode {}
+
+impl Hash for StatusCode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code().hash(state);
+    }
+}
+
 impl StatusCode {
     const ASCII_ZERO: u8 = 48;
 
@@ -60,8 +194,48 @@ impl StatusCode {
             (code % 10) as u8 + Self::ASCII_ZERO,
         ]
     }
+
+    /// 1xx
+    pub const fn is_informational(&self) -> bool {
+        matches!(self.code(), 100..=199)
+    }
+
+    /// 2xx
+    pub const fn is_success(&self) -> bool {
+        matches!(self.code(), 200..=299)
+    }
+
+    /// 3xx
+    pub const fn is_redirection(&self) -> bool {
+        matches!(self.code(), 300..=399)
+    }
+
+    /// 4xx
+    pub const fn is_client_error(&self) -> bool {
+        matches!(self.code(), 400..=499)
+    }
+
+    /// 5xx
+    pub const fn is_server_error(&self) -> bool {
+        matches!(self.code(), 500..=599)
+    }
 }
 
+/// Error of creating `StatusCode` from a number which is not 3 digits or a reason phrase with
+/// control characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidStatusCode(pub u16);
+
+impl fmt::Display for InvalidStatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid status code or reason phrase: {}", self.0)
+    }
+}
+
+impl error::Error for InvalidStatusCode {}
+
+// Variants are generated by `define_status_codes!`, so `#[default]` cannot be attached.
+#[allow(clippy::derivable_impls)]
 impl Default for StatusCode {
     fn default() -> Self {
         StatusCode::Ok
@@ -70,8 +244,14 @@ impl Default for StatusCode {
 
 impl From<io::Error> for StatusCode {
     fn from(err: io::Error) -> Self {
+        err.kind().into()
+    }
+}
+
+impl From<io::ErrorKind> for StatusCode {
+    fn from(kind: io::ErrorKind) -> Self {
         use io::ErrorKind::*;
-        match err.kind() {
+        match kind {
             NotFound => StatusCode::NotFound,
             PermissionDenied => StatusCode::Forbidden,
             _ => StatusCode::InternalServerError,
@@ -84,3 +264,48 @@ impl From<StatusCode> for Response {
         Response::builder().set_status_code(code).build()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_code_from_u16() {
+        assert_eq!(Ok(StatusCode::Created), StatusCode::try_from(201));
+        assert_eq!(Ok(StatusCode::TooManyRequests), StatusCode::try_from(429));
+        let code = StatusCode::try_from(599).unwrap();
+        assert_eq!(599, code.code());
+        assert_eq!(b"", code.reason_phrase());
+        assert_eq!(Err(InvalidStatusCode(99)), StatusCode::try_from(99));
+        assert_eq!(Err(InvalidStatusCode(1000)), StatusCode::try_from(1000));
+    }
+
+    #[test]
+    fn custom_status_code() {
+        let code = StatusCode::custom(599, "Network Connect Timeout Error").unwrap();
+        assert_eq!(599, code.code());
+        assert_eq!(*b"599", code.as_bytes());
+        assert_eq!(b"Network Connect Timeout Error", code.reason_phrase());
+        assert!(code.is_server_error());
+
+        let code = StatusCode::custom(404, "Gone Fishing").unwrap();
+        assert!(matches!(code, StatusCode::NotFound));
+        assert_eq!(b"Not Found", code.reason_phrase());
+        assert_eq!(StatusCode::custom(599, "A"), StatusCode::custom(599, "B"));
+        assert_eq!(Err(InvalidStatusCode(42)), StatusCode::custom(42, "Answer"));
+        assert_eq!(
+            Err(InvalidStatusCode(599)),
+            StatusCode::custom(599, "Evil\r\nSet-Cookie: a=b")
+        );
+    }
+
+    #[test]
+    fn classify_status_code() {
+        assert!(StatusCode::Continue.is_informational());
+        assert!(StatusCode::NoContent.is_success());
+        assert!(StatusCode::PermanentRedirect.is_redirection());
+        assert!(StatusCode::UnprocessableContent.is_client_error());
+        assert!(!StatusCode::UnprocessableContent.is_server_error());
+        assert!(StatusCode::ServiceUnavailable.is_server_error());
+    }
+}
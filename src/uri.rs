@@ -0,0 +1,230 @@
+use crate::status::StatusCode;
+use std::{convert::TryFrom, fmt};
+
+/// URI split into path, query and fragment, e.g. `/search?q=qz#result` is split into `/search`,
+/// `q=qz` and `result`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Uri {
+    path: Vec<u8>,
+    query: Option<Vec<u8>>,
+    fragment: Option<Vec<u8>>,
+}
+
+impl Uri {
+    pub fn new(uri: &[u8]) -> Self {
+        let (uri, fragment) = match uri.iter().position(|&b| b == b'#') {
+            Some(index) => (&uri[..index], Some(uri[(index + 1)..].to_vec())),
+            None => (uri, None),
+        };
+        let (path, query) = match uri.iter().position(|&b| b == b'?') {
+            Some(index) => (&uri[..index], Some(uri[(index + 1)..].to_vec())),
+            None => (uri, None),
+        };
+        Self {
+            path: path.to_vec(),
+            query,
+            fragment,
+        }
+    }
+
+    /// Path as it is sent, i.e. it is not percent-decoded.
+    pub fn path(&self) -> &[u8] {
+        &self.path
+    }
+
+    /// Percent-decoded and normalized path. See `UriPath`.
+    pub fn decoded_path(&self) -> crate::Result<UriPath, StatusCode> {
+        UriPath::try_from(&self.path[..])
+    }
+
+    /// Query string without leading `?`.
+    pub fn query(&self) -> Option<&[u8]> {
+        self.query.as_deref()
+    }
+
+    /// Fragment without leading `#`.
+    pub fn fragment(&self) -> Option<&[u8]> {
+        self.fragment.as_deref()
+    }
+
+    /// Whole of the URI.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.path.clone();
+        if let Some(query) = &self.query {
+            bytes.push(b'?');
+            bytes.extend_from_slice(query);
+        }
+        if let Some(fragment) = &self.fragment {
+            bytes.push(b'#');
+            bytes.extend_from_slice(fragment);
+        }
+        bytes
+    }
+}
+
+impl From<&str> for Uri {
+    fn from(s: &str) -> Self {
+        Uri::new(s.as_bytes())
+    }
+}
+
+impl Default for Uri {
+    fn default() -> Self {
+        Uri::new(b"/")
+    }
+}
+
+impl fmt::Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.to_bytes()))
+    }
+}
+
+/// Percent-decoded and normalized path of a URI, e.g. `/static/./a//../my%20file.txt` is
+/// `/static/my file.txt`.
+///
+/// `.` and `..` segments are resolved and consecutive slashes are merged, so the path never goes
+/// above the root. Paths containing an encoded slash(`%2F`) or a NUL are rejected because they
+/// would be split or truncated differently from the client's intent once decoded.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UriPath(String);
+
+impl UriPath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Segments of the path, e.g. `/static/index.html` has `static` and `index.html`.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/').filter(|segment| !segment.is_empty())
+    }
+}
+
+impl TryFrom<&[u8]> for UriPath {
+    type Error = StatusCode;
+
+    fn try_from(path: &[u8]) -> crate::Result<Self, StatusCode> {
+        let path = path.strip_prefix(b"/").ok_or(StatusCode::BadRequest)?;
+        let mut segments: Vec<String> = Vec::new();
+        // Whether the path refers to a directory, e.g. `/a/`, `/a/.` or `/a/b/..`.
+        let mut trailing_slash = false;
+        for segment in path.split(|&b| b == b'/') {
+            let segment = percent_decode(segment)?;
+            trailing_slash = true;
+            match segment.as_str() {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                _ => {
+                    trailing_slash = false;
+                    segments.push(segment);
+                }
+            }
+        }
+
+        let mut normalized = format!("/{}", segments.join("/"));
+        if trailing_slash && !segments.is_empty() {
+            normalized.push('/');
+        }
+        Ok(Self(normalized))
+    }
+}
+
+impl Default for UriPath {
+    fn default() -> Self {
+        Self("/".to_string())
+    }
+}
+
+impl fmt::Display for UriPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Decode a segment of a path.
+fn percent_decode(segment: &[u8]) -> crate::Result<String, StatusCode> {
+    let mut decoded = Vec::with_capacity(segment.len());
+    let mut bytes = segment.iter();
+    while let Some(&b) = bytes.next() {
+        let b = if b == b'%' {
+            let high = bytes.next().and_then(|&b| (b as char).to_digit(16));
+            let low = bytes.next().and_then(|&b| (b as char).to_digit(16));
+            match (high, low) {
+                (Some(high), Some(low)) => (high * 16 + low) as u8,
+                _ => return Err(StatusCode::BadRequest),
+            }
+        } else {
+            b
+        };
+        // An encoded slash would be taken as a separator of segments after decoding, and NUL
+        // terminates a path on some platforms.
+        if b == b'/' || b == b'\0' {
+            return Err(StatusCode::BadRequest);
+        }
+        decoded.push(b);
+    }
+    String::from_utf8(decoded).or(Err(StatusCode::BadRequest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_uri() {
+        let uri = Uri::new(b"/search?q=qz&page=2#result");
+        assert_eq!(b"/search", uri.path());
+        assert_eq!(Some(&b"q=qz&page=2"[..]), uri.query());
+        assert_eq!(Some(&b"result"[..]), uri.fragment());
+        assert_eq!(b"/search?q=qz&page=2#result".to_vec(), uri.to_bytes());
+    }
+
+    #[test]
+    fn split_uri_without_query() {
+        let uri = Uri::new(b"/index.html#top");
+        assert_eq!(b"/index.html", uri.path());
+        assert_eq!(None, uri.query());
+        assert_eq!(Some(&b"top"[..]), uri.fragment());
+    }
+
+    fn decode(path: &str) -> crate::Result<String, StatusCode> {
+        UriPath::try_from(path.as_bytes()).map(|path| path.to_string())
+    }
+
+    #[test]
+    fn decode_path() {
+        assert_eq!(Ok("/my file.txt".to_string()), decode("/my%20file.txt"));
+        assert_eq!(Ok("/日本".to_string()), decode("/%E6%97%A5%e6%9c%ac"));
+        assert_eq!(Ok("/a+b".to_string()), decode("/a+b"));
+    }
+
+    #[test]
+    fn normalize_path() {
+        assert_eq!(Ok("/".to_string()), decode("/"));
+        assert_eq!(Ok("/a/b".to_string()), decode("//a///b"));
+        assert_eq!(Ok("/a/".to_string()), decode("/a/"));
+        assert_eq!(Ok("/a/".to_string()), decode("/a/./"));
+        assert_eq!(Ok("/b".to_string()), decode("/a/../b"));
+        assert_eq!(Ok("/".to_string()), decode("/a/.."));
+        assert_eq!(
+            Ok("/secret.txt".to_string()),
+            decode("/static/../../secret.txt")
+        );
+        assert_eq!(
+            Ok("/secret.txt".to_string()),
+            decode("/static/%2e%2E/secret.txt")
+        );
+    }
+
+    #[test]
+    fn reject_invalid_path() {
+        assert_eq!(Err(StatusCode::BadRequest), decode("/a%2Fb"));
+        assert_eq!(Err(StatusCode::BadRequest), decode("/a%00.txt"));
+        assert_eq!(Err(StatusCode::BadRequest), decode("/a%2"));
+        assert_eq!(Err(StatusCode::BadRequest), decode("/a%zz"));
+        assert_eq!(Err(StatusCode::BadRequest), decode("/%ff"));
+        assert_eq!(Err(StatusCode::BadRequest), decode("a/b"));
+    }
+}
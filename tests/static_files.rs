@@ -1,12 +1,21 @@
 use qz::{
-    body::Body,
     handler::Handler,
     header::HeaderName,
     request::RequestBuffer,
+    response::Response,
     static_files::{StaticDir, StaticFile},
     status::StatusCode,
+    Error,
 };
 
+// Bodies of static files are streamed, so read them by sending responses.
+async fn read_body(response: &Response) -> Vec<u8> {
+    let mut sent = Vec::new();
+    response.send(&mut sent).await.unwrap();
+    let body_start = sent.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    sent.split_off(body_start)
+}
+
 #[tokio::test]
 async fn static_file() -> qz::Result<()> {
     let mut buffer = RequestBuffer::new();
@@ -26,7 +35,7 @@ async fn static_file() -> qz::Result<()> {
         Some(&b"text/html".to_vec()),
         response.get_header(&HeaderName::ContentType)
     );
-    assert_eq!(&Body::from(&b"<p>Hello</p>\n"[..]), response.body());
+    assert_eq!(b"<p>Hello</p>\n".to_vec(), read_body(&response).await);
     Ok(())
 }
 
@@ -49,7 +58,7 @@ async fn static_dir() -> qz::Result<()> {
         Some(&b"text/html".to_vec()),
         response.get_header(&HeaderName::ContentType)
     );
-    assert_eq!(&Body::from(&b"<p>Hello</p>\n"[..]), response.body());
+    assert_eq!(b"<p>Hello</p>\n".to_vec(), read_body(&response).await);
     Ok(())
 }
 
@@ -62,6 +71,183 @@ async fn static_dir_not_found() -> qz::Result<()> {
     let request = buffer.complete();
 
     let handler = StaticDir::mount("./tests/assets", "/assets");
-    assert_eq!(Err(StatusCode::NotFound), handler.call(request, ()).await);
+    assert_eq!(
+        Err(Error::from(StatusCode::NotFound)),
+        handler.call(request, ()).await
+    );
+    Ok(())
+}
+
+async fn call_with_range(range: &str) -> qz::Result<Response> {
+    let mut buffer = RequestBuffer::new();
+    buffer
+        .try_parse(format!("GET /index.html HTTP/1.1\r\nRange: {}\r\n\r\n", range).as_bytes())
+        .unwrap();
+    let request = buffer.complete();
+    let handler = StaticFile::mount("./tests/index.html")?;
+    handler.call(request, ()).await
+}
+
+#[tokio::test]
+async fn single_range() -> qz::Result<()> {
+    let response = call_with_range("bytes=3-7").await?;
+    assert_eq!(StatusCode::PartialContent, response.status_code());
+    assert_eq!(
+        Some(&b"bytes 3-7/13".to_vec()),
+        response.get_header(&HeaderName::ContentRange)
+    );
+    assert_eq!(
+        Some(&b"bytes".to_vec()),
+        response.get_header(&HeaderName::AcceptRanges)
+    );
+    assert_eq!(b"Hello".to_vec(), read_body(&response).await);
+    Ok(())
+}
+
+#[tokio::test]
+async fn multiple_ranges() -> qz::Result<()> {
+    let response = call_with_range("bytes=0-2,-5").await?;
+    assert_eq!(StatusCode::PartialContent, response.status_code());
+    let content_type = response.get_header(&HeaderName::ContentType).unwrap();
+    let content_type = std::str::from_utf8(content_type).unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/byteranges; boundary=")
+        .unwrap();
+    let expected = format!(
+        "--{b}\r\nContent-Type: text/html\r\nContent-Range: bytes 0-2/13\r\n\r\n<p>\
+        \r\n--{b}\r\nContent-Type: text/html\r\nContent-Range: bytes 8-12/13\r\n\r\n</p>\n\
+        \r\n--{b}--\r\n",
+        b = boundary
+    );
+    assert_eq!(
+        Some(&expected.len().to_string().into_bytes()),
+        response.get_header(&HeaderName::ContentLength)
+    );
+    assert_eq!(expected.into_bytes(), read_body(&response).await);
+    Ok(())
+}
+
+#[tokio::test]
+async fn coalesced_ranges() -> qz::Result<()> {
+    let ranges = (0..13)
+        .map(|i| format!("{}-{}", i, i))
+        .collect::<Vec<_>>()
+        .join(",");
+    let response = call_with_range(&format!("bytes={}", ranges)).await?;
+    assert_eq!(StatusCode::PartialContent, response.status_code());
+    assert_eq!(
+        Some(&b"bytes 0-12/13".to_vec()),
+        response.get_header(&HeaderName::ContentRange)
+    );
+    assert_eq!(b"<p>Hello</p>\n".to_vec(), read_body(&response).await);
+    Ok(())
+}
+
+#[tokio::test]
+async fn too_many_ranges() -> qz::Result<()> {
+    let path = std::env::temp_dir().join(format!("qz-too-many-ranges-{}.txt", std::process::id()));
+    std::fs::write(&path, [b'a'; 100])?;
+    let ranges = (0..17)
+        .map(|i| format!("{}-{}", i * 2, i * 2))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut buffer = RequestBuffer::new();
+    buffer
+        .try_parse(format!("GET / HTTP/1.1\r\nRange: bytes={}\r\n\r\n", ranges).as_bytes())
+        .unwrap();
+    let handler = StaticFile::mount(&path)?;
+    let response = handler.call(buffer.complete(), ()).await?;
+    assert_eq!(StatusCode::Ok, response.status_code());
+    assert_eq!(vec![b'a'; 100], read_body(&response).await);
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn range_not_satisfiable() -> qz::Result<()> {
+    let response = call_with_range("bytes=13-").await?;
+    assert_eq!(StatusCode::RangeNotSatisfiable, response.status_code());
+    assert_eq!(
+        Some(&b"bytes */13".to_vec()),
+        response.get_header(&HeaderName::ContentRange)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn if_range_mismatch() -> qz::Result<()> {
+    let mut buffer = RequestBuffer::new();
+    buffer
+        .try_parse(
+            b"GET /index.html HTTP/1.1\r\nRange: bytes=3-7\r\n\
+            If-Range: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n",
+        )
+        .unwrap();
+    let request = buffer.complete();
+    let handler = StaticFile::mount("./tests/index.html")?;
+    let response = handler.call(request, ()).await?;
+    assert_eq!(StatusCode::Ok, response.status_code());
+    assert_eq!(b"<p>Hello</p>\n".to_vec(), read_body(&response).await);
+    Ok(())
+}
+
+async fn call_with_header(header: &str) -> qz::Result<Response> {
+    let mut buffer = RequestBuffer::new();
+    buffer
+        .try_parse(format!("GET /assets/index.html HTTP/1.1\r\n{}\r\n\r\n", header).as_bytes())
+        .unwrap();
+    let request = buffer.complete();
+    let handler = StaticDir::mount("./tests/assets", "/assets").cache_control("max-age=60");
+    handler.call(request, ()).await
+}
+
+#[tokio::test]
+async fn validators() -> qz::Result<()> {
+    let response = call_with_header("Accept: */*").await?;
+    assert_eq!(StatusCode::Ok, response.status_code());
+    assert!(response.get_header(&HeaderName::ETag).is_some());
+    assert!(response.get_header(&HeaderName::LastModified).is_some());
+    assert_eq!(
+        Some(&b"max-age=60".to_vec()),
+        response.get_header(&HeaderName::CacheControl)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn if_none_match() -> qz::Result<()> {
+    let response = call_with_header("Accept: */*").await?;
+    let etag = std::str::from_utf8(response.get_header(&HeaderName::ETag).unwrap()).unwrap();
+
+    let response = call_with_header(&format!("If-None-Match: \"other\", W/{}", etag)).await?;
+    assert_eq!(StatusCode::NotModified, response.status_code());
+    assert_eq!(b"".to_vec(), read_body(&response).await);
+
+    let response = call_with_header("If-None-Match: \"other\"").await?;
+    assert_eq!(StatusCode::Ok, response.status_code());
+    Ok(())
+}
+
+#[tokio::test]
+async fn if_modified_since() -> qz::Result<()> {
+    let response = call_with_header("Accept: */*").await?;
+    let last_modified =
+        std::str::from_utf8(response.get_header(&HeaderName::LastModified).unwrap()).unwrap();
+
+    let response = call_with_header(&format!("If-Modified-Since: {}", last_modified)).await?;
+    assert_eq!(StatusCode::NotModified, response.status_code());
+
+    let response = call_with_header("If-Modified-Since: Sun, 06 Nov 1994 08:49:37 GMT").await?;
+    assert_eq!(StatusCode::Ok, response.status_code());
+    Ok(())
+}
+
+#[tokio::test]
+async fn if_range_with_etag() -> qz::Result<()> {
+    let response = call_with_header("Accept: */*").await?;
+    let etag = std::str::from_utf8(response.get_header(&HeaderName::ETag).unwrap()).unwrap();
+
+    let response = call_with_header(&format!("Range: bytes=3-7\r\nIf-Range: {}", etag)).await?;
+    assert_eq!(StatusCode::PartialContent, response.status_code());
     Ok(())
 }